@@ -0,0 +1,457 @@
+// src/binlevel.rs
+use std::fmt;
+use std::path::PathBuf;
+use raylib::color::Color;
+use crate::bytes::{ByteReader, ByteWriter, ReadError};
+use crate::caster::Face;
+use crate::decals::DecalSpec;
+use crate::grid::Grid;
+use crate::level::{EmitterSpec, LevelDef, LevelSource, LightSpec, SpriteSpec};
+use crate::maze::{validate_storeys, LoadError, Maze};
+use crate::particles::EmitterParams;
+use crate::sprites::SpriteAnchor;
+use crate::tile::Tile;
+
+/// Formato binario de nivel (`.lvlb`), para niveles grandes ya generados y
+/// partidas guardadas: ocupa mucho menos que el `.lvl` y se lee sin
+/// interpretar texto.
+///
+/// ```text
+/// "LVLB"  versión (u16 LE)
+/// sección*: etiqueta (4 bytes ASCII)  largo (u32 LE)  datos
+/// ```
+///
+/// Secciones: `META` (nombre, autor, par, ángulo, cielo, suelo, niebla, luz
+/// ambiente), `TEXR` (texturas), `GRID` (una por piso, en orden), `LITE`,
+/// `SPRT`, `EMIT` y `DECL` (listas como en el `.lvl`). Enteros en varint,
+/// decimales `f32` y colores RGBA (ver `bytes`).
+///
+/// `GRID`: ancho, alto, la paleta (cuántos tiles distintos y sus caracteres)
+/// y el mapa fila por fila en tramos de un byte: el índice en la paleta en
+/// los bits altos (los justos para la paleta) y el largo - 1 en el resto; un
+/// tramo más largo se parte en varios.
+///
+/// Compatibilidad: un lector salta las secciones que no conoce y los datos
+/// que sobren al final de una que sí conoce, así que se pueden agregar
+/// secciones (p. ej. el estado de una partida) o campos al final sin cambiar
+/// la versión. `VERSION` solo sube si cambia algo que ya existe.
+pub const EXTENSION: &str = "lvlb";
+const MAGIC: &[u8; 4] = b"LVLB";
+const VERSION: u16 = 1;
+
+/// Límites al leer, para que un archivo roto no pida memoria de más: tiles
+/// de todos los pisos juntos (un tramo de un byte puede valer 256 tiles).
+const MAX_TILES: usize = 1 << 24;
+const MAX_STOREYS: usize = 64;
+
+const ANCHORS: [SpriteAnchor; 3] = [SpriteAnchor::Floor, SpriteAnchor::Center, SpriteAnchor::Ceiling];
+const FACES: [Face; 4] = [Face::North, Face::South, Face::East, Face::West];
+
+#[derive(Debug)]
+pub enum BinError {
+    Magic,
+    Version(u16),
+    Section(String, ReadError),
+    NoGrid,
+    Storey(usize),
+    Maze(LoadError),
+}
+
+impl fmt::Display for BinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BinError::Magic          => write!(f, "no es un nivel binario (falta la cabecera LVLB)"),
+            BinError::Version(v)     => write!(f, "versión {v} del formato binario no soportada (hasta {VERSION})"),
+            BinError::Section(tag, e) => write!(f, "sección {tag}: {e}"),
+            BinError::NoGrid         => write!(f, "el nivel no tiene ningún piso (sección GRID)"),
+            BinError::Storey(s)      => write!(f, "hay algo en el piso {}, que no existe", s + 1),
+            BinError::Maze(e)        => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Escribe `def` con los mapas `storeys` (los de un nivel procedural ya
+/// generado; `def.source` no se mira). Las rutas se guardan tal cual.
+pub fn to_bytes(def: &LevelDef, storeys: &[Maze]) -> Vec<u8> {
+    let mut out = ByteWriter::default();
+    out.bytes(MAGIC);
+    out.bytes(&VERSION.to_le_bytes());
+
+    section(&mut out, b"META", |w| {
+        w.str(&def.name);
+        opt(w, def.author.as_ref(), |w, a| w.str(a));
+        opt(w, def.par, ByteWriter::f32);
+        opt(w, def.start_angle, ByteWriter::f32);
+        opt(w, def.sky, color);
+        opt(w, def.floor, color);
+        opt(w, def.fog, |w, (c, dist)| { color(w, c); opt(w, dist, ByteWriter::f32); });
+        opt(w, def.ambient, ByteWriter::f32);
+    });
+    if !def.textures.is_empty() {
+        section(&mut out, b"TEXR", |w| {
+            w.var(def.textures.len() as u64);
+            for (tile, path) in &def.textures {
+                w.byte(tile.to_char() as u8);
+                w.str(path);
+            }
+        });
+    }
+    for maze in storeys {
+        section(&mut out, b"GRID", |w| write_grid(w, maze));
+    }
+    list(&mut out, b"LITE", &def.lights, |w, l| {
+        w.f32(l.x);
+        w.f32(l.y);
+        w.f32(l.radius);
+        color(w, l.color);
+        w.var(l.storey as u64);
+    });
+    list(&mut out, b"SPRT", &def.sprites, |w, s| {
+        w.f32(s.x);
+        w.f32(s.y);
+        w.str(&s.texture);
+        w.f32(s.radius);
+        w.f32(s.size);
+        w.byte(ANCHORS.iter().position(|&a| a == s.anchor).unwrap_or(0) as u8);
+        w.f32(s.z);
+        w.var(s.storey as u64);
+    });
+    list(&mut out, b"EMIT", &def.emitters, |w, e| {
+        w.f32(e.x);
+        w.f32(e.y);
+        w.f32(e.z);
+        let p = &e.params;
+        for v in [p.rate, p.life.0, p.life.1, p.speed.0, p.speed.1, p.up.0, p.up.1, p.spread, p.gravity, p.size.0, p.size.1] {
+            w.f32(v);
+        }
+        color(w, p.color.0);
+        color(w, p.color.1);
+        w.var(e.storey as u64);
+    });
+    list(&mut out, b"DECL", &def.decals, |w, d| {
+        w.var(d.i as u64);
+        w.var(d.j as u64);
+        w.byte(FACES.iter().position(|&f| f == d.face).unwrap_or(0) as u8);
+        w.str(&d.name);
+        for v in [d.rect.0, d.rect.1, d.rect.2, d.rect.3] { w.f32(v); }
+        w.var(d.storey as u64);
+    });
+    out.0
+}
+
+/// Lee un `.lvlb`; `default_name` se usa si no trae nombre. Los mapas pasan
+/// por las mismas comprobaciones que un `.lvl` hecho a mano.
+pub fn from_bytes(bytes: &[u8], default_name: &str, dir: PathBuf) -> Result<LevelDef, BinError> {
+    let mut r = ByteReader::new(bytes);
+    if r.take(4).ok() != Some(MAGIC.as_slice()) { return Err(BinError::Magic); }
+    let version = r.take(2).map(|b| u16::from_le_bytes([b[0], b[1]])).map_err(|_| BinError::Magic)?;
+    if version > VERSION { return Err(BinError::Version(version)); }
+
+    let mut def = LevelDef::new(default_name, dir);
+    let mut storeys = Vec::new();
+    let mut tiles_left = MAX_TILES;
+    while !r.done() {
+        let header = |r: &mut ByteReader<'_>| -> Result<_, ReadError> {
+            let tag = String::from_utf8_lossy(r.take(4)?).into_owned();
+            let len = r.take(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))?;
+            Ok((tag, len as usize))
+        };
+        let (tag, len) = header(&mut r).map_err(|e| BinError::Section("?".into(), e))?;
+        let data = r.take(len).map_err(|e| BinError::Section(tag.clone(), e))?;
+        let mut s = ByteReader::new(data);
+        let read = match tag.as_str() {
+            "META" => read_meta(&mut s, &mut def),
+            "TEXR" => read_list(&mut s, |s| {
+                let tile = Tile::from_char(s.byte()? as char).ok_or(ReadError("tile desconocido"))?;
+                def.textures.push((tile, s.str()?));
+                Ok(())
+            }),
+            "GRID" if storeys.len() >= MAX_STOREYS => Err(ReadError("demasiados pisos")),
+            "GRID" => read_grid(&mut s, &mut tiles_left).map(|m| storeys.push(m)),
+            "LITE" => read_list(&mut s, |s| {
+                def.lights.push(LightSpec { x: s.f32()?, y: s.f32()?, radius: s.f32()?, color: read_color(s)?, storey: s.var()? as usize });
+                Ok(())
+            }),
+            "SPRT" => read_list(&mut s, |s| {
+                def.sprites.push(SpriteSpec {
+                    x: s.f32()?,
+                    y: s.f32()?,
+                    texture: s.str()?,
+                    radius: s.f32()?,
+                    size: s.f32()?,
+                    anchor: *ANCHORS.get(s.byte()? as usize).ok_or(ReadError("ancla inválida"))?,
+                    z: s.f32()?,
+                    storey: s.var()? as usize,
+                });
+                Ok(())
+            }),
+            "EMIT" => read_list(&mut s, |s| {
+                let (x, y, z) = (s.f32()?, s.f32()?, s.f32()?);
+                let mut v = [0.0; 11];
+                for x in &mut v { *x = s.f32()?; }
+                let params = EmitterParams {
+                    rate: v[0],
+                    life: (v[1], v[2]),
+                    speed: (v[3], v[4]),
+                    up: (v[5], v[6]),
+                    spread: v[7],
+                    gravity: v[8],
+                    size: (v[9], v[10]),
+                    color: (read_color(s)?, read_color(s)?),
+                };
+                def.emitters.push(EmitterSpec { x, y, z, params, storey: s.var()? as usize });
+                Ok(())
+            }),
+            "DECL" => read_list(&mut s, |s| {
+                let (i, j) = (s.var()? as usize, s.var()? as usize);
+                let face = *FACES.get(s.byte()? as usize).ok_or(ReadError("cara inválida"))?;
+                let name = s.str()?;
+                let rect = (s.f32()?, s.f32()?, s.f32()?, s.f32()?);
+                def.decals.push(DecalSpec { i, j, face, name, rect, storey: s.var()? as usize });
+                Ok(())
+            }),
+            _ => Ok(()), // sección de una versión más nueva: se salta
+        };
+        read.map_err(|e| BinError::Section(tag, e))?;
+    }
+
+    if storeys.is_empty() { return Err(BinError::NoGrid); }
+    validate_storeys(&storeys, true).map_err(BinError::Maze)?;
+    let top = def.lights.iter().map(|l| l.storey)
+        .chain(def.sprites.iter().map(|s| s.storey))
+        .chain(def.emitters.iter().map(|e| e.storey))
+        .chain(def.decals.iter().map(|d| d.storey))
+        .max();
+    if let Some(s) = top.filter(|&s| s >= storeys.len()) { return Err(BinError::Storey(s)); }
+    def.source = LevelSource::Grid(storeys);
+    Ok(def)
+}
+
+fn section(out: &mut ByteWriter, tag: &[u8; 4], f: impl FnOnce(&mut ByteWriter)) {
+    let mut w = ByteWriter::default();
+    f(&mut w);
+    out.bytes(tag);
+    out.bytes(&(w.0.len() as u32).to_le_bytes());
+    out.bytes(&w.0);
+}
+
+/// Una sección con la cantidad y los elementos (nada si no hay).
+fn list<T>(out: &mut ByteWriter, tag: &[u8; 4], items: &[T], mut f: impl FnMut(&mut ByteWriter, &T)) {
+    if items.is_empty() { return; }
+    section(out, tag, |w| {
+        w.var(items.len() as u64);
+        for it in items { f(w, it); }
+    });
+}
+
+fn read_list(s: &mut ByteReader, mut f: impl FnMut(&mut ByteReader) -> Result<(), ReadError>) -> Result<(), ReadError> {
+    for _ in 0..s.var()? { f(s)?; }
+    Ok(())
+}
+
+/// Opcional: 0, o 1 y el valor.
+fn opt<T>(w: &mut ByteWriter, v: Option<T>, f: impl FnOnce(&mut ByteWriter, T)) {
+    match v {
+        Some(v) => { w.byte(1); f(w, v); }
+        None => w.byte(0),
+    }
+}
+
+fn read_opt<'a, T>(s: &mut ByteReader<'a>, f: impl FnOnce(&mut ByteReader<'a>) -> Result<T, ReadError>) -> Result<Option<T>, ReadError> {
+    match s.byte()? {
+        0 => Ok(None),
+        1 => f(s).map(Some),
+        _ => Err(ReadError("opcional inválido")),
+    }
+}
+
+fn color(w: &mut ByteWriter, c: Color) {
+    w.bytes(&[c.r, c.g, c.b, c.a]);
+}
+
+fn read_color(s: &mut ByteReader) -> Result<Color, ReadError> {
+    let b = s.take(4)?;
+    Ok(Color::new(b[0], b[1], b[2], b[3]))
+}
+
+fn read_meta(s: &mut ByteReader, def: &mut LevelDef) -> Result<(), ReadError> {
+    def.name = s.str()?;
+    def.author = read_opt(s, ByteReader::str)?;
+    def.par = read_opt(s, ByteReader::f32)?;
+    def.start_angle = read_opt(s, ByteReader::f32)?;
+    def.sky = read_opt(s, read_color)?;
+    def.floor = read_opt(s, read_color)?;
+    def.fog = read_opt(s, |s| Ok((read_color(s)?, read_opt(s, ByteReader::f32)?)))?;
+    def.ambient = read_opt(s, ByteReader::f32)?;
+    Ok(())
+}
+
+/// Bits del índice de paleta en cada tramo de `GRID`.
+fn index_bits(palette: usize) -> u32 {
+    usize::BITS - palette.saturating_sub(1).leading_zeros()
+}
+
+fn write_grid(w: &mut ByteWriter, maze: &Maze) {
+    w.var(maze.width() as u64);
+    w.var(maze.height() as u64);
+    let mut palette: Vec<Tile> = Vec::new();
+    for (_, t) in maze.cells() {
+        if !palette.contains(&t) { palette.push(t); }
+    }
+    w.var(palette.len() as u64);
+    for t in &palette { w.byte(t.to_char() as u8); }
+    let run_bits = 8 - index_bits(palette.len());
+    let mut tiles = maze.cells().map(|(_, t)| t).peekable();
+    while let Some(t) = tiles.next() {
+        let index = palette.iter().position(|&p| p == t).unwrap_or(0) as u8;
+        let mut run = 1;
+        while run < 1 << run_bits && tiles.next_if_eq(&t).is_some() { run += 1; }
+        w.byte(((index as u16) << run_bits) as u8 | (run - 1) as u8);
+    }
+}
+
+/// Lee un piso; `tiles_left` es lo que queda de `MAX_TILES` para todo el nivel.
+fn read_grid(s: &mut ByteReader, tiles_left: &mut usize) -> Result<Maze, ReadError> {
+    let (w, h) = (s.var()?, s.var()?);
+    let size = w.checked_mul(h).filter(|&n| n > 0).ok_or(ReadError("tamaño de piso inválido"))?;
+    if size > *tiles_left as u64 { return Err(ReadError("el nivel es demasiado grande")); }
+    *tiles_left -= size as usize;
+    let (w, h) = (w as usize, h as usize);
+    let n = s.var()? as usize;
+    if n == 0 || n > 128 { return Err(ReadError("paleta inválida")); }
+    let palette = (0..n)
+        .map(|_| Tile::from_char(s.byte()? as char).ok_or(ReadError("tile desconocido")))
+        .collect::<Result<Vec<_>, _>>()?;
+    let run_bits = 8 - index_bits(n);
+    let mut maze = Grid::new(w, h, Tile::Wall);
+    let mut k = 0;
+    while k < w * h {
+        let b = s.byte()? as u16;
+        let tile = *palette.get((b >> run_bits) as usize).ok_or(ReadError("tile fuera de la paleta"))?;
+        let run = (b & ((1 << run_bits) - 1)) as usize + 1;
+        if run > w * h - k { return Err(ReadError("tramo fuera del mapa")); }
+        for n in k..k + run { maze[(n % w, n / w)] = tile; }
+        k += run;
+    }
+    Ok(maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::parse_storeys;
+    use crate::particles;
+
+    /// Dos pisos unidos por una escalera, con un poco de todo encima.
+    fn sample() -> (LevelDef, Vec<Maze>) {
+        let storeys = parse_storeys("#######\n#p  <V#\n#######\n@storey\n#######\n#g  > #\n#######\n", true).unwrap();
+        let mut def = LevelDef::new("Prueba", PathBuf::new());
+        def.author = Some("Ana".into());
+        def.par = Some(12.5);
+        def.fog = Some((Color::new(10, 20, 30, 255), Some(8.0)));
+        def.textures.push((Tile::Moss, "musgo.png".into()));
+        def.lights.push(LightSpec { x: 1.5, y: 1.5, radius: 3.0, color: Color::new(255, 200, 120, 255), storey: 1 });
+        def.sprites.push(SpriteSpec {
+            x: 2.5, y: 1.5, texture: "crate".into(), radius: 0.2, size: 0.5,
+            anchor: SpriteAnchor::Center, z: 0.5, storey: 1,
+        });
+        def.emitters.push(EmitterSpec { x: 3.5, y: 1.5, z: 0.2, params: particles::sparkles(), storey: 0 });
+        def.decals.push(DecalSpec { i: 2, j: 0, face: Face::South, name: "arrow".into(), rect: (0.1, 0.2, 0.8, 0.6), storey: 1 });
+        (def, storeys)
+    }
+
+    fn grids(def: &LevelDef) -> &[Maze] {
+        match &def.source {
+            LevelSource::Grid(storeys) => storeys,
+            LevelSource::Procedural(_) => panic!("se esperaba un grid"),
+        }
+    }
+
+    /// Datos de la primera sección (la `META`) y dónde empieza la siguiente.
+    fn first_section(bytes: &[u8]) -> (std::ops::Range<usize>, usize) {
+        let len = u32::from_le_bytes(bytes[10..14].try_into().unwrap()) as usize;
+        (14..14 + len, 14 + len)
+    }
+
+    #[test]
+    fn round_trip_keeps_everything() {
+        let (def, storeys) = sample();
+        let bytes = to_bytes(&def, &storeys);
+        let back = from_bytes(&bytes, "otro", PathBuf::new()).unwrap();
+        assert_eq!(back.name, "Prueba");
+        assert_eq!(back.author.as_deref(), Some("Ana"));
+        assert_eq!(grids(&back), storeys.as_slice());
+        assert_eq!((back.lights.len(), back.sprites.len(), back.emitters.len(), back.decals.len()), (1, 1, 1, 1));
+        assert!(back.decals[0].face == Face::South && back.sprites[0].anchor == SpriteAnchor::Center);
+        assert_eq!(to_bytes(&back, grids(&back)), bytes);
+    }
+
+    #[test]
+    fn grid_palettes_of_any_size() {
+        let one = Grid::new(40, 30, Tile::Wall);
+        let mut two = one.clone();
+        two[(3, 4)] = Tile::Floor;
+        let mut many = one.clone();
+        let chars = "# VDNRM123456789pg<>E";
+        for (k, c) in chars.chars().enumerate() {
+            many[(k % 40, k / 40 + 1)] = Tile::from_char(c).unwrap();
+        }
+        for (maze, n) in [(one, 1), (two, 2), (many, chars.len())] {
+            let mut w = ByteWriter::default();
+            write_grid(&mut w, &maze);
+            let mut tiles_left = MAX_TILES;
+            let back = read_grid(&mut ByteReader::new(&w.0), &mut tiles_left).unwrap();
+            assert_eq!(back, maze, "paleta de {n}");
+            assert_eq!(tiles_left, MAX_TILES - 40 * 30);
+        }
+    }
+
+    #[test]
+    fn unknown_sections_and_trailing_data_are_skipped() {
+        let (def, storeys) = sample();
+        let bytes = to_bytes(&def, &storeys);
+
+        // una sección que este lector no conoce
+        let mut newer = ByteWriter(bytes.clone());
+        section(&mut newer, b"SAVE", |w| w.bytes(&[7; 9]));
+        assert_eq!(to_bytes(&from_bytes(&newer.0, "x", PathBuf::new()).unwrap(), &storeys), bytes);
+
+        // tres bytes de más al final de META
+        let (meta, rest) = first_section(&bytes);
+        let mut longer = bytes[..10].to_vec();
+        longer.extend((meta.len() as u32 + 3).to_le_bytes());
+        longer.extend(&bytes[meta]);
+        longer.extend([1, 2, 3]);
+        longer.extend(&bytes[rest..]);
+        let back = from_bytes(&longer, "x", PathBuf::new()).unwrap();
+        assert_eq!(back.par, Some(12.5));
+        assert_eq!(to_bytes(&back, &storeys), bytes);
+    }
+
+    #[test]
+    fn broken_files_are_reported() {
+        let (def, storeys) = sample();
+        let bytes = to_bytes(&def, &storeys);
+
+        let cut = from_bytes(&bytes[..bytes.len() - 1], "x", PathBuf::new());
+        assert!(matches!(cut, Err(BinError::Section(..))));
+
+        let mut magic = bytes.clone();
+        magic[..4].copy_from_slice(b"LVLX");
+        assert!(matches!(from_bytes(&magic, "x", PathBuf::new()), Err(BinError::Magic)));
+
+        let mut newer = bytes.clone();
+        newer[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert!(matches!(from_bytes(&newer, "x", PathBuf::new()), Err(BinError::Version(v)) if v == VERSION + 1));
+    }
+
+    #[test]
+    fn huge_grids_are_rejected_before_allocating() {
+        let mut w = ByteWriter::default();
+        w.bytes(MAGIC);
+        w.bytes(&VERSION.to_le_bytes());
+        section(&mut w, b"GRID", |w| { w.var(5000); w.var(5000); w.var(1); w.byte(b'#'); });
+        let err = from_bytes(&w.0, "x", PathBuf::new());
+        assert!(matches!(err, Err(BinError::Section(tag, ReadError("el nivel es demasiado grande"))) if tag == "GRID"));
+    }
+}
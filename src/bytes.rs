@@ -0,0 +1,96 @@
+// src/bytes.rs
+use std::fmt;
+
+/// Bytes que se van armando: enteros en varint (LEB128, los chicos ocupan
+/// uno), decimales como `f32` little-endian exactos y textos con su largo.
+#[derive(Default)]
+pub struct ByteWriter(pub Vec<u8>);
+
+impl ByteWriter {
+    pub fn byte(&mut self, b: u8) {
+        self.0.push(b);
+    }
+
+    pub fn bytes(&mut self, b: &[u8]) {
+        self.0.extend_from_slice(b);
+    }
+
+    pub fn var(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.0.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        self.0.push(v as u8);
+    }
+
+    pub fn f32(&mut self, v: f32) {
+        self.0.extend(v.to_le_bytes());
+    }
+
+    pub fn str(&mut self, s: &str) {
+        self.var(s.len() as u64);
+        self.0.extend(s.as_bytes());
+    }
+}
+
+/// Por qué no se pudo leer (en castellano, para mostrarlo tal cual).
+#[derive(Debug)]
+pub struct ReadError(pub &'static str);
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Lee lo que escribe `ByteWriter`, en el mismo orden.
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// ¿Se leyó todo?
+    pub fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    pub fn byte(&mut self) -> Result<u8, ReadError> {
+        let b = *self.bytes.get(self.pos).ok_or(ReadError("faltan datos"))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    pub fn take(&mut self, n: usize) -> Result<&'a [u8], ReadError> {
+        let end = self.pos.checked_add(n).filter(|&e| e <= self.bytes.len()).ok_or(ReadError("faltan datos"))?;
+        let out = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub fn var(&mut self) -> Result<u64, ReadError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.byte()?;
+            v |= ((b & 0x7F) as u64) << shift;
+            if b & 0x80 == 0 { return Ok(v); }
+        }
+        Err(ReadError("número demasiado largo"))
+    }
+
+    pub fn f32(&mut self) -> Result<f32, ReadError> {
+        let b = self.take(4)?;
+        let v = f32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+        if v.is_finite() { Ok(v) } else { Err(ReadError("número no finito")) }
+    }
+
+    pub fn str(&mut self) -> Result<String, ReadError> {
+        let len = self.var()? as usize;
+        let b = self.take(len)?;
+        std::str::from_utf8(b).map(str::to_string).map_err(|_| ReadError("texto no UTF-8"))
+    }
+}
@@ -4,14 +4,86 @@ use raylib::color::Color;
 use crate::framebuffer::Framebuffer;
 use crate::maze::Maze;
 use crate::player::Player;
+use crate::portals::Portals;
+use crate::tile::Tile;
 
-/// Resultado del cast: distancia al muro y qué tipo de pared impactó.
+/// Resultado del cast: distancia al muro y qué tipo de pared impactó
+/// (`Tile::Floor` si el rayo salió del mapa).
+/// Con espejos/portales `distance` es el recorrido total del rayo y
+/// `(hit_x, hit_y)` el punto final; `first_distance` es la distancia a la
+/// primera superficie (espejo o portal), que es lo que debe ocluir sprites.
+/// `segments` son los tramos que siguen a esa superficie (vacío sin rebotes),
+/// para dibujar los sprites que se ven a través de ella.
 pub struct Intersect {
     pub distance: f32,
-    pub impact:   char,
+    pub impact:   Tile,
+    pub hit_x:    f32,
+    pub hit_y:    f32,
+    pub first_distance: f32,
+    pub bounces:  u32,
+    pub segments: Vec<RaySegment>,
+}
+
+/// Tramo recto de un rayo tras un espejo o portal: sale de `(x, y)` con
+/// ángulo `a` y cubre de `start` a `end` en distancia total recorrida.
+#[derive(Clone, Copy, Debug)]
+pub struct RaySegment {
+    pub x: f32,
+    pub y: f32,
+    pub a: f32,
+    pub start: f32,
+    pub end: f32,
+}
+
+/// Cara de un bloque de muro, nombrada por el lado del bloque que mira
+/// (North = lado de `y` menor, West = lado de `x` menor).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Face { North, South, East, West }
+
+impl Face {
+    /// Convierte la U de textura de pared a U "de la cara": crece de izquierda
+    /// a derecha vista desde fuera del muro (no queda espejada).
+    #[inline]
+    pub fn local_u(self, u: f32) -> f32 {
+        match self {
+            Face::West | Face::South => u,
+            Face::East | Face::North => 1.0 - u,
+        }
+    }
+}
+
+/// Dado un punto de impacto en mundo, devuelve la cara golpeada (el borde del
+/// bloque más cercano) y la coordenada U de textura (0..1) a lo largo de ella.
+pub fn hit_face(hit_x: f32, hit_y: f32, block_size: usize) -> (Face, f32) {
+    let bs = block_size as i32;
+    let lx = ((hit_x as i32 % bs) + bs) % bs;
+    let ly = ((hit_y as i32 % bs) + bs) % bs;
+
+    let to_left   = lx;
+    let to_right  = (bs - 1) - lx;
+    let to_top    = ly;
+    let to_bottom = (bs - 1) - ly;
+
+    let span = (block_size.saturating_sub(1)).max(1) as f32;
+    if to_left.min(to_right) <= to_top.min(to_bottom) {
+        let face = if to_left <= to_right { Face::West } else { Face::East };
+        (face, ly as f32 / span)
+    } else {
+        let face = if to_top <= to_bottom { Face::North } else { Face::South };
+        (face, lx as f32 / span)
+    }
+}
+
+const STEP: f32 = 10.0;
+
+/// Cierra el tramo actual en `at` y abre uno nuevo desde `(x, y)` con ángulo `a`.
+fn turn(segments: &mut Vec<RaySegment>, x: f32, y: f32, a: f32, at: f32) {
+    if let Some(s) = segments.last_mut() { s.end = at; }
+    segments.push(RaySegment { x, y, a, start: at, end: f32::INFINITY });
 }
 
 /// Lanza un rayo desde la posición del jugador en ángulo `a`.
+/// Rebota en espejos y atraviesa portales hasta `portals.max_depth` veces.
 /// Si `draw_line == true`, además traza la línea en el minimapa.
 pub fn cast_ray(
     framebuffer: &mut Framebuffer,
@@ -19,31 +91,78 @@ pub fn cast_ray(
     player: &Player,
     a: f32,
     block_size: usize,
+    portals: &Portals,
     draw_line: bool,
 ) -> Intersect {
-    let mut d = 0.0;
     framebuffer.set_current_color(Color::WHITESMOKE);
 
+    // Segmento actual: origen + ángulo; `travelled` = largo de los anteriores
+    let (mut ox, mut oy, mut ang) = (player.pos.x, player.pos.y, a);
+    let mut d = 0.0;
+    let mut travelled = 0.0;
+    let mut first: Option<f32> = None;
+    let mut segments: Vec<RaySegment> = Vec::new();
+    let mut depth = 0;
+    let mut prev = (player.pos.x as usize / block_size, player.pos.y as usize / block_size);
+
     loop {
-        // Avanzamos un “paso” d en la dirección a
-        let dx = d * a.cos();
-        let dy = d * a.sin();
-        let x = (player.pos.x + dx) as usize;
-        let y = (player.pos.y + dy) as usize;
+        // Avanzamos un “paso” d en la dirección ang
+        let fx = ox + d * ang.cos();
+        let fy = oy + d * ang.sin();
+        let (x, y) = (fx.max(0.0) as usize, fy.max(0.0) as usize);
 
         // Índices de celda
         let i = x / block_size;
         let j = y / block_size;
+        let done = |impact: Tile, mut segments: Vec<RaySegment>| {
+            if let Some(s) = segments.last_mut() { s.end = travelled + d; }
+            Intersect {
+                distance: travelled + d,
+                impact,
+                hit_x: fx,
+                hit_y: fy,
+                first_distance: first.unwrap_or(travelled + d),
+                bounces: depth,
+                segments,
+            }
+        };
 
         // Si salimos del mapa, devolvemos distancia actual
-        if j >= maze.len() || i >= maze[0].len() {
-            return Intersect { distance: d, impact: ' ' };
+        let Some(cell) = maze.get(i, j).filter(|_| fx >= 0.0 && fy >= 0.0) else {
+            return done(Tile::Floor, std::mem::take(&mut segments));
+        };
+
+        // Espejo: volvemos al paso anterior y reflejamos según el borde cruzado
+        if cell.is_mirror() && depth < portals.max_depth && d > 0.0 {
+            first.get_or_insert(travelled + d);
+            ox += (d - STEP) * ang.cos();
+            oy += (d - STEP) * ang.sin();
+            travelled += d - STEP;
+            if prev.0 != i { ang = std::f32::consts::PI - ang; }
+            if prev.1 != j { ang = -ang; }
+            turn(&mut segments, ox, oy, ang, travelled);
+            d = STEP;
+            depth += 1;
+            continue;
+        }
+
+        // Portal: al entrar desde fuera, seguimos desde el otro extremo
+        let entering = cell.is_portal() && prev != (i, j) && !maze[prev].is_portal();
+        if let Some(link) = portals.get(i, j).filter(|_| entering && depth < portals.max_depth) {
+            first.get_or_insert(travelled + d);
+            (ox, oy) = Portals::transform(link, (i, j), fx, fy, block_size);
+            ang += link.rot;
+            travelled += d;
+            turn(&mut segments, ox, oy, ang, travelled);
+            prev = link.to;
+            d = STEP;
+            depth += 1;
+            continue;
         }
 
-        let cell = maze[j][i];
-        // Si choca contra algo que no sea espacio/p/g
-        if cell != ' ' && cell != 'p' && cell != 'g' {
-            return Intersect { distance: d, impact: cell };
+        // Si choca contra algo opaco (muro o espejo agotado)
+        if cell.is_opaque() {
+            return done(cell, std::mem::take(&mut segments));
         }
 
         // Dibuja la línea en el minimapa 2D
@@ -52,6 +171,7 @@ pub fn cast_ray(
         }
 
         // Incrementa distancia
-        d += 10.0;
+        prev = (i, j);
+        d += STEP;
     }
 }
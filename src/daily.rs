@@ -0,0 +1,78 @@
+// src/daily.rs
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::level::{parse_level, LevelDef};
+
+/// Fecha del calendario (UTC, para que sea el mismo día para todos).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Hoy, según el reloj del sistema.
+    pub fn today() -> Self {
+        let secs = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self::from_days((secs / 86_400) as i64)
+    }
+
+    /// Días desde 1970-01-01 -> fecha (algoritmo "civil_from_days" de H. Hinnant).
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + (month <= 2) as i64;
+        Self { year, month, day }
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Semilla del día: la fecha como número (AAAAMMDD) mezclada.
+pub fn daily_seed(date: Date) -> u64 {
+    let n = date.year as u64 * 10_000 + date.month as u64 * 100 + date.day as u64;
+    let mut z = n.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 31)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    (z ^ (z >> 29)) % 1_000_000_000 // corta, para poder dictarla
+}
+
+/// Nivel del desafío diario. El generador y los parámetros son fijos: solo
+/// cambia la semilla, así que todos juegan el mismo laberinto ese día. El id
+/// (`diario-AAAA-MM-DD`) separa los récords de cada día.
+pub fn daily_level(date: Date) -> LevelDef {
+    let text = format!(
+        "@name Desafío del día {date}\n\
+         @generator wilson 18 13\n\
+         @seed {}\n\
+         @braid 20\n\
+         @mirrors 2\n\
+         @portals 1\n\
+         @placement diameter\n\
+         @walls voronoi 6\n\
+         @ambient 0.7\n",
+        daily_seed(date),
+    );
+    parse_level(&text, &format!("diario-{date}"), PathBuf::new()).expect("nivel diario inválido")
+}
+
+/// Resultado para pegar en el chat, p. ej.
+/// `Laberinto diario 2026-10-19: 1:23.4 | recorrido 112% del óptimo | sin pista`.
+pub fn result_line(date: Date, seconds: f32, walked: f32, optimal: usize, used_hint: bool) -> String {
+    let tenths = (seconds.max(0.0) * 10.0).round() as u32; // 59.96 s -> 1:00.0, no 0:60.0
+    let (min, sec) = (tenths / 600, (tenths % 600) as f32 / 10.0);
+    let ratio = 100.0 * walked / optimal.max(1) as f32;
+    let hint = if used_hint { "con pista" } else { "sin pista" };
+    format!("Laberinto diario {date}: {min}:{sec:04.1} | recorrido {ratio:.0}% del óptimo | {hint}")
+}
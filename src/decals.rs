@@ -0,0 +1,337 @@
+// src/decals.rs
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::{caster::Face, texture::Texture};
+
+/// Calcomanía sobre una cara de muro. `rect` = (u0, v0, u1, v1) en
+/// coordenadas de la cara (0..1, U de izquierda a derecha vista desde fuera).
+#[derive(Clone)]
+pub struct Decal {
+    pub tex: Rc<Texture>,
+    pub rect: (f32, f32, f32, f32),
+    pub flip_u: bool,
+    pub chalk: bool, // pintada por el jugador (se reemplaza al repintar)
+}
+
+/// Calcomanías por (celda, cara), en orden de dibujo.
+#[derive(Default)]
+pub struct DecalMap {
+    map: HashMap<(usize, usize, Face), Vec<Decal>>,
+}
+
+impl DecalMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, i: usize, j: usize, face: Face, decal: Decal) {
+        self.map.entry((i, j, face)).or_default().push(decal);
+    }
+
+    pub fn get(&self, i: usize, j: usize, face: Face) -> &[Decal] {
+        self.map.get(&(i, j, face)).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// Pinta una marca de tiza: sustituye la marca anterior de esa cara.
+    pub fn paint_chalk(&mut self, i: usize, j: usize, face: Face, decal: Decal) {
+        let list = self.map.entry((i, j, face)).or_default();
+        list.retain(|d| !d.chalk);
+        list.push(Decal { chalk: true, ..decal });
+    }
+}
+
+/// Mezcla (alpha) las calcomanías sobre el texel base 0xAARRGGBB.
+pub fn composite(decals: &[Decal], u: f32, v: f32, base: u32) -> u32 {
+    let mut out = base;
+    for d in decals {
+        let (u0, v0, u1, v1) = d.rect;
+        if u < u0 || u >= u1 || v < v0 || v >= v1 { continue; }
+        let mut du = (u - u0) / (u1 - u0);
+        let dv = (v - v0) / (v1 - v0);
+        if d.flip_u { du = 1.0 - du; }
+
+        let src = d.tex.sample(du, dv);
+        let a = (src >> 24) & 0xFF;
+        if a == 0 { continue; }
+        let mix = |shift: u32| {
+            let s = (src >> shift) & 0xFF;
+            let t = (out >> shift) & 0xFF;
+            ((s * a + t * (255 - a)) / 255) << shift
+        };
+        out = (out & 0xFF00_0000) | mix(16) | mix(8) | mix(0);
+    }
+    out
+}
+
+/// Texturas de calcomanía por nombre (las integradas + las que se carguen).
+pub struct DecalLibrary {
+    map: HashMap<String, Rc<Texture>>,
+}
+
+impl DecalLibrary {
+    /// Biblioteca con las calcomanías procedurales: "arrow", "x" y "blood".
+    pub fn builtin() -> Self {
+        let mut map = HashMap::new();
+        map.insert("arrow".to_string(), Rc::new(chalk_arrow()));
+        map.insert("x".to_string(),     Rc::new(chalk_x()));
+        map.insert("blood".to_string(), Rc::new(blood_splat()));
+        Self { map }
+    }
+
+    /// Busca por nombre; si no existe, lo escribe si es un letrero
+    /// (`text:...`) o intenta cargarlo como imagen (carteles).
+    pub fn resolve(&mut self, name: &str) -> Option<Rc<Texture>> {
+        if let Some(t) = self.map.get(name) {
+            return Some(t.clone());
+        }
+        let tex = match name.strip_prefix(TEXT_PREFIX) {
+            Some(text) => Rc::new(text_sign(text)),
+            None => Rc::new(Texture::from_file(name).ok()?),
+        };
+        self.map.insert(name.to_string(), tex.clone());
+        Some(tex)
+    }
+}
+
+/// Una línea de calcomanía en un archivo de nivel:
+/// `@decal <i> <j> <N|S|E|W> <nombre|ruta> [u0 v0 u1 v1]`, o un letrero:
+/// `@decal <i> <j> <N|S|E|W> text "<texto>" [u0 v0 u1 v1]` (queda con
+/// nombre `text:<texto>`; sin rectángulo, se ajusta al largo del texto).
+#[derive(Clone)]
+pub struct DecalSpec {
+    pub i: usize,
+    pub j: usize,
+    pub face: Face,
+    pub name: String,
+    pub rect: (f32, f32, f32, f32),
+    pub storey: usize,
+}
+
+pub fn parse_decal_line(line: &str) -> Result<DecalSpec, String> {
+    // el texto entre comillas va aparte: puede tener espacios
+    let (head, text, tail) = match line.split_once('"') {
+        Some((head, rest)) => {
+            let (text, tail) = rest.split_once('"').ok_or("falta la comilla de cierre del letrero")?;
+            (head, Some(text), tail)
+        }
+        None => (line, None, ""),
+    };
+    let mut parts: Vec<&str> = head.split_whitespace().collect();
+    let name = match text {
+        Some(text) => {
+            if parts.len() != 5 || parts[4] != "text" {
+                return Err(format!("letrero inválido: '{line}' (uso: @decal i j cara text \"...\")"));
+            }
+            if text.chars().count() > MAX_SIGN_CHARS {
+                return Err(format!("letrero demasiado largo (hasta {MAX_SIGN_CHARS} letras)"));
+            }
+            format!("{TEXT_PREFIX}{text}")
+        }
+        None => parts.get(4).map_or_else(String::new, |s| s.to_string()),
+    };
+    parts.extend(tail.split_whitespace());
+    if parts.first() != Some(&"@decal") || (parts.len() != 5 && parts.len() != 9) {
+        return Err(format!("calcomanía inválida: '{line}'"));
+    }
+    let num = |s: &str| s.parse::<f32>().map_err(|_| format!("número inválido '{s}'"));
+    let i = parts[1].parse().map_err(|_| format!("columna inválida '{}'", parts[1]))?;
+    let j = parts[2].parse().map_err(|_| format!("fila inválida '{}'", parts[2]))?;
+    let face = match parts[3] {
+        "N" => Face::North,
+        "S" => Face::South,
+        "E" => Face::East,
+        "W" => Face::West,
+        f   => return Err(format!("cara inválida '{f}' (N/S/E/W)")),
+    };
+    let rect = match (parts.len(), text) {
+        (9, _) => (num(parts[5])?, num(parts[6])?, num(parts[7])?, num(parts[8])?),
+        (_, Some(text)) => sign_rect(text),
+        _ => DEFAULT_RECT,
+    };
+    Ok(DecalSpec { i, j, face, name, rect, storey: 0 })
+}
+
+/// Rectángulo por defecto: centrado, a media altura.
+pub const DEFAULT_RECT: (f32, f32, f32, f32) = (0.25, 0.3, 0.75, 0.7);
+
+// --- Texturas procedurales ---
+
+const DECAL_RES: u32 = 32;
+
+fn procedural(f: impl Fn(f32, f32) -> u32) -> Texture {
+    let mut px = Vec::with_capacity((DECAL_RES * DECAL_RES) as usize);
+    for y in 0..DECAL_RES {
+        for x in 0..DECAL_RES {
+            let u = (x as f32 + 0.5) / DECAL_RES as f32;
+            let v = (y as f32 + 0.5) / DECAL_RES as f32;
+            px.push(f(u, v));
+        }
+    }
+    Texture { w: DECAL_RES, h: DECAL_RES, px }
+}
+
+const CHALK: u32 = 0xE6_F0F0E8;
+
+/// Flecha de tiza apuntando a la derecha.
+pub fn chalk_arrow() -> Texture {
+    procedural(|u, v| {
+        let shaft = (0.1..0.6).contains(&u) && (v - 0.5).abs() < 0.08;
+        let head  = (0.55..0.9).contains(&u) && (v - 0.5).abs() < (0.9 - u) * 0.9;
+        if shaft || head { CHALK } else { 0 }
+    })
+}
+
+/// Cruz de tiza ("ya pasé por aquí").
+pub fn chalk_x() -> Texture {
+    procedural(|u, v| {
+        let inside = (0.15..0.85).contains(&u) && (0.15..0.85).contains(&v);
+        if inside && ((u - v).abs() < 0.08 || (u + v - 1.0).abs() < 0.08) { CHALK } else { 0 }
+    })
+}
+
+// --- Letreros ---
+
+/// Prefijo de nombre de las calcomanías de texto.
+pub const TEXT_PREFIX: &str = "text:";
+const MAX_SIGN_CHARS: usize = 32;
+
+/// Fuente de 5x7: cada fila es un byte con los 5 bits bajos (el alto a la izquierda).
+const FONT: [(char, [u8; 7]); 45] = [
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    ('!', [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('/', [0b00001, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b10000]),
+    ('>', [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000]),
+    ('<', [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010]),
+];
+
+/// Filas de la letra `c` (en mayúscula, sin tilde); lo que no está, '?'.
+fn glyph(c: char) -> [u8; 7] {
+    let c = match c.to_uppercase().next().unwrap_or(c) {
+        'Á' | 'À' => 'A',
+        'É' | 'È' => 'E',
+        'Í' | 'Ì' => 'I',
+        'Ó' | 'Ò' => 'O',
+        'Ú' | 'Ù' | 'Ü' => 'U',
+        'Ñ' => 'N',
+        '¡' => '!',
+        '¿' => '?',
+        c => c,
+    };
+    if c == ' ' { return [0; 7]; }
+    FONT.iter().find(|&&(ch, _)| ch == c).or_else(|| FONT.iter().find(|&&(ch, _)| ch == '?')).map_or([0; 7], |g| g.1)
+}
+
+const SIGN_BG: u32 = 0xE0_2A2118;
+const SIGN_INK: u32 = 0xFF_F2E6C8;
+
+/// Letrero: el texto en la fuente de 5x7 sobre una placa oscura, con un
+/// margen de 2 píxeles (6 de ancho por letra, contando el espacio).
+pub fn text_sign(text: &str) -> Texture {
+    let glyphs: Vec<[u8; 7]> = text.chars().map(glyph).collect();
+    let (w, h) = (sign_size(glyphs.len()), 11);
+    let mut px = vec![SIGN_BG; (w * h) as usize];
+    for (k, g) in glyphs.iter().enumerate() {
+        for (row, bits) in g.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0b10000 >> col) != 0 {
+                    let (x, y) = (2 + 6 * k as u32 + col, 2 + row as u32);
+                    px[(y * w + x) as usize] = SIGN_INK;
+                }
+            }
+        }
+    }
+    Texture { w, h, px }
+}
+
+fn sign_size(chars: usize) -> u32 {
+    6 * chars.max(1) as u32 + 3
+}
+
+/// Rectángulo de un letrero sin rectángulo propio: centrado, algo arriba de
+/// la mitad y con las proporciones del texto (hasta el 90% del ancho).
+fn sign_rect(text: &str) -> (f32, f32, f32, f32) {
+    let aspect = sign_size(text.chars().count()) as f32 / 11.0;
+    let h = 0.16f32.min(0.9 / aspect);
+    let w = h * aspect;
+    (0.5 - w / 2.0, 0.35 - h / 2.0, 0.5 + w / 2.0, 0.35 + h / 2.0)
+}
+
+/// Mancha de sangre (gotas fijas alrededor de una mancha central).
+pub fn blood_splat() -> Texture {
+    const DROPS: [(f32, f32, f32); 6] = [
+        (0.5, 0.5, 0.22), (0.28, 0.35, 0.08), (0.75, 0.4, 0.07),
+        (0.62, 0.78, 0.09), (0.3, 0.7, 0.06), (0.5, 0.15, 0.05),
+    ];
+    procedural(|u, v| {
+        let hit = DROPS.iter().any(|&(cx, cy, r)| (u - cx).hypot(v - cy) < r);
+        if hit { 0xD0_7A0A0A } else { 0 }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_lines() {
+        let d = parse_decal_line(r#"@decal 4 2 N text "Salida ->""#).unwrap();
+        assert_eq!((d.i, d.j, d.face, d.name.as_str()), (4, 2, Face::North, "text:Salida ->"));
+        assert!(d.rect.0 > 0.0 && d.rect.2 < 1.0 && d.rect.0 < d.rect.2);
+
+        let d = parse_decal_line(r#"@decal 1 1 W text "A B" 0.1 0.2 0.9 0.4"#).unwrap();
+        assert_eq!((d.name.as_str(), d.rect), ("text:A B", (0.1, 0.2, 0.9, 0.4)));
+
+        assert!(parse_decal_line(r#"@decal 1 1 W text "sin cerrar"#).is_err());
+        assert!(parse_decal_line(r#"@decal 1 1 W arrow "suelto""#).is_err());
+        assert_eq!(parse_decal_line("@decal 4 2 W arrow").unwrap().rect, DEFAULT_RECT);
+    }
+
+    #[test]
+    fn sign_texture_fits_the_text() {
+        let t = text_sign("Ñu 1!");
+        assert_eq!((t.w, t.h), (6 * 5 + 3, 11));
+        assert!(t.px.contains(&SIGN_INK));
+        assert_eq!(glyph('ñ'), glyph('N'));
+        assert_eq!(glyph('@'), glyph('?'));
+    }
+}
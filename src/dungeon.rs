@@ -0,0 +1,175 @@
+// src/dungeon.rs
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::grid::Grid;
+use crate::maze::{retile_walls_stripes, Maze, MazeReport};
+use crate::solver::{distance_field, Cell};
+use crate::tile::Tile;
+
+/// Sala rectangular; `(x, y)` es la esquina superior izquierda del interior
+/// (en tiles) y `w`×`h` su tamaño, sin contar los muros.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Room {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+impl Room {
+    pub fn center(&self) -> Cell {
+        (self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    pub fn contains(&self, (i, j): Cell) -> bool {
+        i >= self.x && i < self.x + self.w && j >= self.y && j < self.y + self.h
+    }
+
+    /// Las cuatro esquinas interiores (NO, NE, SO, SE).
+    pub fn corners(&self) -> [Cell; 4] {
+        let (r, b) = (self.x + self.w - 1, self.y + self.h - 1);
+        [(self.x, self.y), (r, self.y), (self.x, b), (r, b)]
+    }
+
+    /// ¿Se tocan o quedan sin al menos `gap` tiles de muro entre medio?
+    fn too_close(&self, o: &Room, gap: usize) -> bool {
+        self.x < o.x + o.w + gap && o.x < self.x + self.w + gap
+            && self.y < o.y + o.h + gap && o.y < self.y + self.h + gap
+    }
+}
+
+/// Parámetros del generador de salas.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DungeonOptions {
+    pub max_rooms: usize,
+    pub min_size: usize, // lado mínimo de una sala (tiles)
+    pub max_size: usize,
+    pub extra: u8,       // % de conexiones extra (ciclos) además del árbol
+}
+
+impl Default for DungeonOptions {
+    fn default() -> Self {
+        Self { max_rooms: 8, min_size: 3, max_size: 6, extra: 15 }
+    }
+}
+
+/// Resultado: el mapa (con 'p' y 'g') y las salas, para poner sprites,
+/// luces u objetos por sala.
+pub struct Dungeon {
+    pub maze: Maze,
+    pub rooms: Vec<Room>,
+    pub report: MazeReport,
+}
+
+/// Salas al azar unidas por pasillos en L: primero un árbol de expansión
+/// mínima (distancia Manhattan entre centros) para que todo sea alcanzable,
+/// y luego `extra`% de las conexiones con vecinas cercanas para formar ciclos.
+/// El mapa mide lo mismo que un laberinto de `cell_w`×`cell_h` celdas.
+/// Inicio y meta quedan en los centros de las dos salas más alejadas a pie.
+///
+/// La misma `seed` produce siempre la misma mazmorra.
+pub fn make_dungeon(cell_w: usize, cell_h: usize, seed: u64, opts: DungeonOptions) -> Dungeon {
+    let (w, h) = (cell_w * 2 + 1, cell_h * 2 + 1);
+    let mut maze = Grid::new(w, h, Tile::Wall);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Salas: intentos al azar, descartando las que se pisan
+    let max_side = opts.max_size.min(w - 2).min(h - 2).max(1);
+    let min_side = opts.min_size.clamp(1, max_side);
+    let mut rooms: Vec<Room> = Vec::new();
+    let max_rooms = opts.max_rooms.max(1);
+    for _ in 0..max_rooms * 10 {
+        if rooms.len() >= max_rooms { break; }
+        let rw = rng.gen_range(min_side..=max_side);
+        let rh = rng.gen_range(min_side..=max_side);
+        let room = Room {
+            x: rng.gen_range(1..=w - 1 - rw),
+            y: rng.gen_range(1..=h - 1 - rh),
+            w: rw,
+            h: rh,
+        };
+        if rooms.iter().all(|r| !room.too_close(r, 1)) {
+            rooms.push(room);
+        }
+    }
+    for r in &rooms {
+        for j in r.y..r.y + r.h {
+            for i in r.x..r.x + r.w {
+                maze[(i, j)] = Tile::Floor;
+            }
+        }
+    }
+
+    // Árbol de expansión mínima (Prim sobre el grafo completo)
+    let n = rooms.len();
+    let dist = |a: usize, b: usize| {
+        let (ca, cb) = (rooms[a].center(), rooms[b].center());
+        ca.0.abs_diff(cb.0) + ca.1.abs_diff(cb.1)
+    };
+    let mut linked = vec![vec![false; n]; n];
+    let mut in_tree = vec![false; n];
+    in_tree[0] = true; // el primer intento siempre entra: hay al menos una sala
+    for _ in 1..n {
+        let (a, b) = (0..n)
+            .filter(|&a| in_tree[a])
+            .flat_map(|a| (0..n).filter(|&b| !in_tree[b]).map(move |b| (a, b)))
+            .min_by_key(|&(a, b)| dist(a, b))
+            .expect("quedan salas fuera del árbol");
+        in_tree[b] = true;
+        linked[a][b] = true;
+        linked[b][a] = true;
+        carve_corridor(&mut maze, rooms[a].center(), rooms[b].center(), &mut rng);
+    }
+
+    // Conexiones extra: solo con las 3 salas más cercanas, para no cruzar el mapa
+    let mut loops_added = 0;
+    for a in 0..n {
+        let mut near: Vec<usize> = (0..n).filter(|&b| b != a).collect();
+        near.sort_by_key(|&b| dist(a, b));
+        for &b in near.iter().take(3) {
+            if linked[a][b] || rng.gen_range(0..100) >= opts.extra as u32 { continue; }
+            linked[a][b] = true;
+            linked[b][a] = true;
+            carve_corridor(&mut maze, rooms[a].center(), rooms[b].center(), &mut rng);
+            loops_added += 1;
+        }
+    }
+
+    // Inicio y meta: extremos del recorrido más largo entre centros de sala
+    let farthest_room = |from: usize| {
+        let field = distance_field(&maze, rooms[from].center());
+        (0..n)
+            .filter_map(|k| field.get(rooms[k].center()).map(|d| (d, k)))
+            .max_by_key(|&(d, k)| (d, std::cmp::Reverse(k)))
+            .map_or((from, 0), |(d, k)| (k, d))
+    };
+    let (start_room, _) = farthest_room(0);
+    let (goal_room, mut steps) = farthest_room(start_room);
+    let start = rooms[start_room].center();
+    let mut goal = rooms[goal_room].center();
+    if goal == start {
+        // Una sola sala: la meta va al tile más lejano del centro
+        (goal, steps) = distance_field(&maze, start).farthest().unwrap_or((start, 0));
+    }
+    maze[start] = Tile::Start;
+    maze[goal] = Tile::Goal;
+
+    retile_walls_stripes(&mut maze);
+    let report = MazeReport { loops_added, path_len: steps as usize / 2 };
+    Dungeon { maze, rooms, report }
+}
+
+/// Pasillo de un tile de ancho en forma de L entre dos puntos (el codo va
+/// al azar en una de las dos esquinas).
+fn carve_corridor(maze: &mut Maze, (ax, ay): Cell, (bx, by): Cell, rng: &mut StdRng) {
+    let elbow = if rng.gen_bool(0.5) { (bx, ay) } else { (ax, by) };
+    for (from, to) in [((ax, ay), elbow), (elbow, (bx, by))] {
+        let (x0, x1) = (from.0.min(to.0), from.0.max(to.0));
+        let (y0, y1) = (from.1.min(to.1), from.1.max(to.1));
+        for j in y0..=y1 {
+            for i in x0..=x1 {
+                if maze[(i, j)].is_solid() { maze[(i, j)] = Tile::Floor; }
+            }
+        }
+    }
+}
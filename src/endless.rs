@@ -0,0 +1,82 @@
+// src/endless.rs
+use std::path::PathBuf;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use crate::level::{parse_level, LevelDef};
+
+/// Generadores que se turnan en el modo sin fin (nombres de `@generator`).
+const GENERATORS: [&str; 8] = ["dfs", "prim", "kruskal", "wilson", "eller", "division", "tree-mixed-50", "rooms"];
+
+/// Una carrera del modo sin fin: cada meta lleva al nivel siguiente, un poco
+/// más grande, más oscuro y con más espejos y portales. Todo sale de `seed`,
+/// así que la misma semilla repite la misma carrera.
+pub struct EndlessRun {
+    pub seed: u64,
+    pub depth: usize, // nivel actual (desde 1)
+    pub total: f32,   // segundos de los niveles ya terminados
+    pub def: LevelDef,
+}
+
+impl EndlessRun {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, depth: 1, total: 0.0, def: endless_level(seed, 1) }
+    }
+
+    /// Semilla del nivel actual.
+    pub fn level_seed(&self) -> u64 {
+        level_seed(self.seed, self.depth)
+    }
+
+    /// Suma el tiempo del nivel terminado y pasa al siguiente.
+    pub fn advance(&mut self, level_time: f32) {
+        self.total += level_time;
+        self.depth += 1;
+        self.def = endless_level(self.seed, self.depth);
+    }
+}
+
+fn level_seed(run_seed: u64, depth: usize) -> u64 {
+    run_seed ^ (depth as u64).wrapping_mul(0xD1B5_4A32_D192_ED03)
+}
+
+/// El nivel `depth` de la carrera, escrito como un `.lvl` procedural.
+pub fn endless_level(run_seed: u64, depth: usize) -> LevelDef {
+    let mut rng = StdRng::seed_from_u64(level_seed(run_seed, depth));
+    let d = depth.max(1);
+    let generator = GENERATORS.choose(&mut rng).copied().unwrap_or("dfs");
+    let (w, h) = ((6 + 2 * d).min(30), (4 + 3 * d / 2).min(22));
+    let storeys = 1 + (d >= 6) as usize + (d >= 12) as usize;
+    // cada vez más oscuro: menos luz ambiente, niebla más cerca
+    let ambient = (0.9 - 0.05 * d as f32).max(0.25);
+    let fog = (16usize.saturating_sub(d)).max(5);
+    let tone = 40u8.saturating_sub(3 * d.min(12) as u8);
+
+    let mut text = format!(
+        "@name Sin fin - profundidad {d}\n\
+         @generator {generator} {w} {h}\n\
+         @braid {}\n\
+         @mirrors {}\n\
+         @portals {}\n\
+         @ambient {ambient:.2}\n\
+         @fog {tone} {tone} {} {fog}\n\
+         @sky {tone} {tone} {}\n",
+        (5 * d).min(60),
+        (d / 2).min(12),
+        (d / 3).min(4),
+        tone + 10,
+        tone + 10,
+    );
+    if generator == "rooms" {
+        text.push_str("@roomlight 3 255 190 110\n");
+    } else {
+        text.push_str("@placement diameter\n");
+    }
+    if storeys > 1 {
+        text.push_str(&format!("@storeys {storeys} stairs\n@walls depth\n"));
+    } else {
+        text.push_str("@walls noise 5\n");
+    }
+    parse_level(&text, &format!("sin-fin-{run_seed}-{d}"), PathBuf::new())
+        .expect("nivel sin fin inválido")
+}
@@ -1,12 +1,12 @@
 use raylib::prelude::*;
-use raylib::consts::{GamepadAxis, GamepadButton};
-use crate::{player::Player, maze::Maze};
+use crate::{player::Player, maze::Maze, portals::Portals};
 
 const PLAYER_SPEED: f32 = 180.0;  // px/s
 const ROT_SPEED: f32    = 2.2;    // rad/s (teclas)
 const MOUSE_SENS: f32   = 0.0025; // rad/pixel
-const GAMEPAD_DEADZONE: f32 = 0.20;
 
+/// Procesa entrada y mueve al jugador. Devuelve `true` si el movimiento
+/// chocó contra una pared del mapa en este frame.
 pub fn process_events(
     rl: &RaylibHandle,
     player: &mut Player,
@@ -14,7 +14,7 @@ pub fn process_events(
     block_size: usize,
     dt: f32,
     obstacles: &[(f32,f32,f32)], // <-- (x, y, r)
-) {
+) -> bool {
     // Rotación teclas + mouse
     if rl.is_key_down(KeyboardKey::KEY_LEFT)  { player.a -= ROT_SPEED * dt; }
     if rl.is_key_down(KeyboardKey::KEY_RIGHT) { player.a += ROT_SPEED * dt; }
@@ -34,21 +34,25 @@ pub fn process_events(
     // Colisiones contra mapa (slide) + 
     let r = (block_size as f32) * 0.20;
 
+    let mut bumped = false;
+
     // mover en X
     let new_x = player.pos.x + dx;
-    if can_move_maze(maze, block_size, new_x, player.pos.y, r)
-        && !blocked_by_obstacles(new_x, player.pos.y, r, obstacles)
-    {
+    if !can_move_maze(maze, block_size, new_x, player.pos.y, r) {
+        bumped |= dx != 0.0;
+    } else if !blocked_by_obstacles(new_x, player.pos.y, r, obstacles) {
         player.pos.x = new_x;
     }
 
     // mover en Y
     let new_y = player.pos.y + dy;
-    if can_move_maze(maze, block_size, player.pos.x, new_y, r)
-        && !blocked_by_obstacles(player.pos.x, new_y, r, obstacles)
-    {
+    if !can_move_maze(maze, block_size, player.pos.x, new_y, r) {
+        bumped |= dy != 0.0;
+    } else if !blocked_by_obstacles(player.pos.x, new_y, r, obstacles) {
         player.pos.y = new_y;
     }
+
+    bumped
 }
 
 fn blocked_by_obstacles(x: f32, y: f32, r: f32, obs: &[(f32,f32,f32)]) -> bool {
@@ -68,9 +72,19 @@ fn can_move_maze(maze: &Maze, block_size: usize, x: f32, y: f32, r: f32) -> bool
 
 fn is_walkable(maze: &Maze, block_size: usize, x: f32, y: f32) -> bool {
     if x < 0.0 || y < 0.0 { return false; }
-    let i = (x as usize) / block_size;
-    let j = (y as usize) / block_size;
-    if j >= maze.len() || i >= maze[0].len() { return false; }
-    let c = maze[j][i];
-    c == ' ' || c == 'p' || c == 'g'
+    maze.at_world(x, y, block_size).is_some_and(|t| t.is_walkable())
+}
+
+/// Si el jugador acaba de entrar a un portal (desde fuera de uno), lo lleva
+/// al otro extremo con el giro correspondiente.
+pub fn apply_portals(player: &mut Player, prev: Vector2, maze: &Maze, portals: &Portals, block_size: usize) {
+    let cell = |p: Vector2| (p.x as usize / block_size, p.y as usize / block_size);
+    let (pi, pj) = cell(prev);
+    let (i, j) = cell(player.pos);
+    if (pi, pj) == (i, j) || maze.get(pi, pj).is_none_or(|t| t.is_portal()) { return; }
+    if let Some(link) = portals.get(i, j) {
+        let (x, y) = Portals::transform(link, (i, j), player.pos.x, player.pos.y, block_size);
+        player.pos = Vector2::new(x, y);
+        player.a += link.rot;
+    }
 }
@@ -0,0 +1,178 @@
+// src/export.rs
+use std::fmt::Write as _;
+use image::{ImageResult, Rgb, RgbImage};
+use crate::maze::Maze;
+use crate::solver::Route;
+use crate::tile::Tile;
+
+/// Capas opcionales sobre el plano. Todo en coordenadas de tile (el centro
+/// del tile (i, j) es (i + 0.5, j + 0.5)).
+#[derive(Default)]
+pub struct Overlays<'a> {
+    pub solution: Option<&'a Route>,      // camino más corto
+    pub trail: &'a [(f32, f32)],          // recorrido real del jugador
+    pub sprites: &'a [(f32, f32)],        // posiciones de sprites
+    pub obstacles: &'a [(f32, f32, f32)], // (x, y, radio) de colisión
+}
+
+const SOLUTION: [u8; 3] = [255, 140, 0];
+const TRAIL: [u8; 3] = [220, 30, 60];
+const SPRITE: [u8; 3] = [140, 90, 40];
+
+/// Colores de impresión (fondo claro, muros oscuros).
+fn tile_rgb(t: Tile) -> [u8; 3] {
+    match t {
+        Tile::Wall => [40, 40, 40],
+        Tile::Stone => [110, 110, 110],
+        Tile::Moss => [70, 110, 60],
+        Tile::DarkBrick => [25, 20, 20],
+        Tile::DarkStone => [60, 60, 75],
+        Tile::Sandstone => [170, 140, 90],
+        Tile::Mirror => [170, 220, 255],
+        Tile::Portal(_) => [160, 60, 200],
+        Tile::Start => [80, 160, 255],
+        Tile::Goal => [60, 200, 80],
+        Tile::StairsUp => [255, 160, 0],
+        Tile::StairsDown => [130, 100, 70],
+        Tile::Elevator => [230, 190, 0],
+        Tile::Floor => [255, 255, 255],
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Grid en texto, igual que un archivo de nivel. Sobre el suelo: '.' =
+/// solución, '+' = recorrido del jugador, '*' = sprite u obstáculo.
+pub fn to_text(maze: &Maze, ov: &Overlays) -> String {
+    let mut grid: Vec<Vec<char>> = maze.rows().map(|r| r.iter().map(|t| t.to_char()).collect()).collect();
+    let mut mark = |x: f32, y: f32, ch: char| {
+        if x < 0.0 || y < 0.0 { return; }
+        let cell = grid.get_mut(y as usize).and_then(|r| r.get_mut(x as usize));
+        if let Some(c) = cell.filter(|c| **c == ' ' || **c == '.') {
+            *c = ch;
+        }
+    };
+    for &(i, j) in ov.solution.map_or(&[][..], |r| &r.cells) {
+        mark(i as f32, j as f32, '.');
+    }
+    for &(x, y) in ov.trail {
+        mark(x, y, '+');
+    }
+    for (x, y) in ov.sprites.iter().copied().chain(ov.obstacles.iter().map(|&(x, y, _)| (x, y))) {
+        mark(x, y, '*');
+    }
+    grid.iter().map(|row| row.iter().collect::<String>() + "\n").collect()
+}
+
+/// Plano PNG, `scale` píxeles por tile.
+pub fn save_png(maze: &Maze, ov: &Overlays, scale: u32, path: &str) -> ImageResult<()> {
+    let (w, h) = (maze.width() as u32, maze.height() as u32);
+    let mut img = RgbImage::new(w * scale, h * scale);
+    for ((i, j), t) in maze.cells() {
+        let col = Rgb(tile_rgb(t));
+        for y in 0..scale {
+            for x in 0..scale {
+                img.put_pixel(i as u32 * scale + x, j as u32 * scale + y, col);
+            }
+        }
+    }
+
+    let s = scale as f32;
+    let px = |(x, y): (f32, f32)| (x * s, y * s);
+    for &(x, y, r) in ov.obstacles {
+        disc(&mut img, px((x, y)), (r * s).max(1.0), SPRITE, false);
+    }
+    for &p in ov.sprites {
+        disc(&mut img, px(p), s * 0.15, SPRITE, true);
+    }
+    if let Some(route) = ov.solution {
+        let pts: Vec<(f32, f32)> = route.cells.iter().map(|&(i, j)| px((i as f32 + 0.5, j as f32 + 0.5))).collect();
+        polyline(&mut img, &pts, s * 0.12, SOLUTION);
+    }
+    let trail: Vec<(f32, f32)> = ov.trail.iter().map(|&p| px(p)).collect();
+    polyline(&mut img, &trail, s * 0.08, TRAIL);
+
+    img.save(path)
+}
+
+/// Disco (relleno) o circunferencia en píxeles de imagen.
+fn disc(img: &mut RgbImage, (cx, cy): (f32, f32), r: f32, col: [u8; 3], fill: bool) {
+    let (w, h) = img.dimensions();
+    let x0 = (cx - r - 1.0).max(0.0) as u32;
+    let y0 = (cy - r - 1.0).max(0.0) as u32;
+    let x1 = ((cx + r + 1.0) as u32).min(w);
+    let y1 = ((cy + r + 1.0) as u32).min(h);
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let d = (x as f32 + 0.5 - cx).hypot(y as f32 + 0.5 - cy);
+            if d <= r && (fill || d >= r - 1.0) {
+                img.put_pixel(x, y, Rgb(col));
+            }
+        }
+    }
+}
+
+/// Línea gruesa entre puntos consecutivos (discos a lo largo de cada tramo).
+fn polyline(img: &mut RgbImage, pts: &[(f32, f32)], half: f32, col: [u8; 3]) {
+    let half = half.max(0.5);
+    for seg in pts.windows(2) {
+        let ((ax, ay), (bx, by)) = (seg[0], seg[1]);
+        let steps = ((bx - ax).hypot(by - ay) / half).ceil().max(1.0) as usize;
+        for k in 0..=steps {
+            let t = k as f32 / steps as f32;
+            disc(img, (ax + (bx - ax) * t, ay + (by - ay) * t), half, col, true);
+        }
+    }
+}
+
+/// Plano SVG (escalable, para imprimir); `scale` = unidades por tile.
+pub fn to_svg(maze: &Maze, ov: &Overlays, scale: f32) -> String {
+    let (w, h) = (maze.width() as f32, maze.height() as f32);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {w} {h}">"#,
+        w * scale, h * scale,
+    );
+    let _ = writeln!(out, r#"<rect width="{w}" height="{h}" fill="{}"/>"#, hex(tile_rgb(Tile::Floor)));
+
+    // Tiles no-suelo, agrupando tramos horizontales del mismo tipo
+    for (j, row) in maze.rows().enumerate() {
+        let mut i = 0;
+        while i < row.len() {
+            let c = row[i];
+            let run = row[i..].iter().take_while(|&&d| d == c).count();
+            if c != Tile::Floor {
+                let _ = writeln!(
+                    out,
+                    r#"<rect x="{i}" y="{j}" width="{run}" height="1" fill="{}"/>"#,
+                    hex(tile_rgb(c)),
+                );
+            }
+            i += run;
+        }
+    }
+
+    let points = |pts: &mut dyn Iterator<Item = (f32, f32)>| {
+        pts.map(|(x, y)| format!("{x:.2},{y:.2}")).collect::<Vec<_>>().join(" ")
+    };
+    let line_style = r#"fill="none" stroke-linecap="round" stroke-linejoin="round""#;
+    if let Some(route) = ov.solution {
+        let pts = points(&mut route.cells.iter().map(|&(i, j)| (i as f32 + 0.5, j as f32 + 0.5)));
+        let _ = writeln!(out, r#"<polyline points="{pts}" stroke="{}" stroke-width="0.25" {line_style}/>"#, hex(SOLUTION));
+    }
+    if ov.trail.len() > 1 {
+        let pts = points(&mut ov.trail.iter().copied());
+        let _ = writeln!(out, r#"<polyline points="{pts}" stroke="{}" stroke-width="0.15" {line_style}/>"#, hex(TRAIL));
+    }
+    for &(x, y, r) in ov.obstacles {
+        let _ = writeln!(out, r#"<circle cx="{x:.2}" cy="{y:.2}" r="{r:.2}" fill="none" stroke="{}" stroke-width="0.05"/>"#, hex(SPRITE));
+    }
+    for &(x, y) in ov.sprites {
+        let _ = writeln!(out, r#"<circle cx="{x:.2}" cy="{y:.2}" r="0.15" fill="{}"/>"#, hex(SPRITE));
+    }
+    out.push_str("</svg>\n");
+    out
+}
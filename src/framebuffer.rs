@@ -34,6 +34,17 @@ impl Framebuffer {
         }
     }
 
+    /// Mezcla `color` sobre el píxel existente usando su alpha.
+    pub fn blend_pixel(&mut self, x: u32, y: u32, color: Color) {
+        if x < self.width && y < self.height {
+            let idx = (y * self.width + x) as usize;
+            let dst = self.buffer[idx];
+            let a = color.a as u32;
+            let mix = |s: u8, d: u8| ((s as u32 * a + d as u32 * (255 - a)) / 255) as u8;
+            self.buffer[idx] = Color::new(mix(color.r, dst.r), mix(color.g, dst.g), mix(color.b, dst.b), 255);
+        }
+    }
+
     pub fn clear(&mut self, color: Color) {
         self.buffer.fill(color);
     }
@@ -0,0 +1,492 @@
+// src/generators.rs
+use rand::{Rng, RngCore};
+use rand::seq::SliceRandom;
+use std::collections::VecDeque;
+use crate::grid::Grid;
+use crate::maze::Maze;
+use crate::tile::Tile;
+
+/// Conjunto de bits de tamaño fijo (1 bit por celda).
+#[derive(Clone)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(n: usize) -> Self {
+        Self { words: vec![0; n.div_ceil(64)] }
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> bool {
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, i: usize, v: bool) {
+        if v { self.words[i / 64] |= 1 << (i % 64); }
+        else { self.words[i / 64] &= !(1 << (i % 64)); }
+    }
+}
+
+/// Laberinto en celdas, guardado en forma compacta (3 bits por celda: celda
+/// abierta, pasaje al este, pasaje al sur). En el `Maze` la celda
+/// (x, y) es el tile (2x+1, 2y+1) y el muro entre dos vecinas el tile intermedio.
+/// Así se pueden generar laberintos de millones de celdas sin el costo de un
+/// `Grid` completo, y volcarlos fila a fila con `row`.
+pub struct Carver {
+    pub cw: usize,
+    pub ch: usize,
+    cells: BitSet,
+    east:  BitSet,
+    south: BitSet,
+}
+
+impl Carver {
+    /// Todo muros.
+    pub fn new(cw: usize, ch: usize) -> Self {
+        let n = cw * ch;
+        Self { cw, ch, cells: BitSet::new(n), east: BitSet::new(n), south: BitSet::new(n) }
+    }
+
+    #[inline]
+    pub fn idx(&self, x: usize, y: usize) -> usize {
+        y * self.cw + x
+    }
+
+    pub fn open(&mut self, x: usize, y: usize) {
+        let i = self.idx(x, y);
+        self.cells.set(i, true);
+    }
+
+    /// Pasaje entre dos vecinas: se guarda en la celda de arriba/izquierda.
+    fn passage(&mut self, a: (usize, usize), b: (usize, usize), v: bool) {
+        let (lo, hi) = if (a.1, a.0) < (b.1, b.0) { (a, b) } else { (b, a) };
+        let i = self.idx(lo.0, lo.1);
+        if hi.1 == lo.1 { self.east.set(i, v); } else { self.south.set(i, v); }
+    }
+
+    /// Abre ambas celdas y el muro entre ellas (deben ser vecinas).
+    pub fn link(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.open(a.0, a.1);
+        self.open(b.0, b.1);
+        self.passage(a, b, true);
+    }
+
+    /// Cierra el muro entre dos celdas vecinas.
+    pub fn unlink(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.passage(a, b, false);
+    }
+
+    /// ¿Hay pasaje entre estas dos celdas vecinas?
+    pub fn linked(&self, a: (usize, usize), b: (usize, usize)) -> bool {
+        let (lo, hi) = if (a.1, a.0) < (b.1, b.0) { (a, b) } else { (b, a) };
+        let i = self.idx(lo.0, lo.1);
+        if hi.1 == lo.1 { self.east.get(i) } else { self.south.get(i) }
+    }
+
+    /// Número de pasajes que salen de la celda (1 = callejón sin salida).
+    pub fn degree(&self, x: usize, y: usize) -> usize {
+        self.neighbors(x, y).into_iter().filter(|&n| self.linked((x, y), n)).count()
+    }
+
+    pub fn neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut out = Vec::with_capacity(4);
+        if y > 0           { out.push((x, y - 1)); }
+        if x + 1 < self.cw { out.push((x + 1, y)); }
+        if y + 1 < self.ch { out.push((x, y + 1)); }
+        if x > 0           { out.push((x - 1, y)); }
+        out
+    }
+
+    /// Fila `j` del `Maze` (0..=2*ch), calculada al vuelo.
+    pub fn row(&self, j: usize) -> Vec<Tile> {
+        let mut out = vec![Tile::Wall; self.cw * 2 + 1];
+        if j.is_multiple_of(2) {
+            // fila de muros: pasajes al sur de la fila de celdas de arriba
+            if j == 0 || j == self.ch * 2 { return out; }
+            let y = j / 2 - 1;
+            for x in 0..self.cw {
+                if self.south.get(self.idx(x, y)) { out[x * 2 + 1] = Tile::Floor; }
+            }
+        } else {
+            let y = j / 2;
+            for x in 0..self.cw {
+                let i = self.idx(x, y);
+                if self.cells.get(i) { out[x * 2 + 1] = Tile::Floor; }
+                if self.east.get(i)  { out[x * 2 + 2] = Tile::Floor; }
+            }
+        }
+        out
+    }
+
+    pub fn to_maze(&self) -> Maze {
+        Grid::from_rows((0..self.ch * 2 + 1).map(|j| self.row(j)).collect())
+    }
+}
+
+/// "Trenza" el laberinto: elimina ~`percent`% de los callejones sin salida
+/// abriéndolos hacia un vecino (de preferencia otro callejón). Cada pasaje
+/// nuevo en un laberinto conexo cierra un ciclo; devuelve cuántos se agregaron.
+pub fn braid(c: &mut Carver, percent: u8, rng: &mut dyn RngCore) -> usize {
+    if percent == 0 { return 0; }
+    let mut ends: Vec<(usize, usize)> = (0..c.ch)
+        .flat_map(|y| (0..c.cw).map(move |x| (x, y)))
+        .filter(|&(x, y)| c.degree(x, y) == 1)
+        .collect();
+    ends.shuffle(rng);
+
+    let mut loops = 0;
+    for (x, y) in ends {
+        // puede haber dejado de ser callejón por un pasaje anterior
+        if c.degree(x, y) != 1 || rng.gen_range(0..100) >= percent as u32 { continue; }
+        let closed: Vec<_> = c.neighbors(x, y).into_iter().filter(|&n| !c.linked((x, y), n)).collect();
+        let ends_too: Vec<_> = closed.iter().copied().filter(|&(nx, ny)| c.degree(nx, ny) == 1).collect();
+        let pick = if ends_too.is_empty() { closed.choose(rng) } else { ends_too.choose(rng) };
+        if let Some(&n) = pick {
+            c.link((x, y), n);
+            loops += 1;
+        }
+    }
+    loops
+}
+
+/// Algoritmo que talla pasillos en un `Carver` lleno de muros.
+pub trait MazeGenerator {
+    fn name(&self) -> &'static str;
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore);
+}
+
+/// Cómo elige celda el growing-tree entre las activas.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TreeBias {
+    Newest,      // = backtracker: pasillos largos
+    Oldest,      // pasillos rectos y radiales
+    Random,      // = Prim: muchas ramas cortas
+    Middle,      // la del medio (aproximada: ver `GrowingTree::carve`)
+    Mixed(u8),   // % de veces Newest, el resto Random
+}
+
+/// Algoritmos disponibles, para elegir desde la configuración de nivel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    Eller,
+    GrowingTree(TreeBias),
+    BinaryTree,
+    Division,
+}
+
+impl Algorithm {
+    pub fn generator(self) -> Box<dyn MazeGenerator> {
+        match self {
+            Algorithm::Backtracker    => Box::new(Backtracker),
+            Algorithm::Prim           => Box::new(Prim),
+            Algorithm::Kruskal        => Box::new(Kruskal),
+            Algorithm::Wilson         => Box::new(Wilson),
+            Algorithm::Eller          => Box::new(Eller),
+            Algorithm::GrowingTree(b) => Box::new(GrowingTree(b)),
+            Algorithm::BinaryTree     => Box::new(BinaryTree),
+            Algorithm::Division       => Box::new(Division),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.generator().name()
+    }
+}
+
+/// DFS con backtracking (el generador original), con pila explícita para
+/// no desbordar la pila del programa en grids grandes.
+pub struct Backtracker;
+
+impl MazeGenerator for Backtracker {
+    fn name(&self) -> &'static str { "DFS" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let mut visited = BitSet::new(c.cw * c.ch);
+        let mut stack = vec![(0, 0)];
+        visited.set(0, true);
+        c.open(0, 0);
+
+        while let Some(&(x, y)) = stack.last() {
+            let free: Vec<_> = c.neighbors(x, y).into_iter()
+                .filter(|&(nx, ny)| !visited.get(c.idx(nx, ny)))
+                .collect();
+            match free.choose(rng) {
+                Some(&(nx, ny)) => {
+                    visited.set(c.idx(nx, ny), true);
+                    c.link((x, y), (nx, ny));
+                    stack.push((nx, ny));
+                }
+                None => { stack.pop(); }
+            }
+        }
+    }
+}
+
+/// Prim aleatorio: crece desde una celda eligiendo bordes al azar.
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn name(&self) -> &'static str { "Prim" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let mut in_maze = BitSet::new(c.cw * c.ch);
+        let mut frontier: Vec<((usize, usize), (usize, usize))> = Vec::new();
+
+        let start = (rng.gen_range(0..c.cw), rng.gen_range(0..c.ch));
+        in_maze.set(c.idx(start.0, start.1), true);
+        c.open(start.0, start.1);
+        for n in c.neighbors(start.0, start.1) { frontier.push((start, n)); }
+
+        while !frontier.is_empty() {
+            let (from, to) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            if in_maze.get(c.idx(to.0, to.1)) { continue; }
+            in_maze.set(c.idx(to.0, to.1), true);
+            c.link(from, to);
+            for n in c.neighbors(to.0, to.1) {
+                if !in_maze.get(c.idx(n.0, n.1)) { frontier.push((to, n)); }
+            }
+        }
+    }
+}
+
+/// Kruskal aleatorio: une conjuntos (union-find) en orden de muros barajado.
+pub struct Kruskal;
+
+fn find(parent: &mut [usize], mut a: usize) -> usize {
+    while parent[a] != a {
+        parent[a] = parent[parent[a]];
+        a = parent[a];
+    }
+    a
+}
+
+impl MazeGenerator for Kruskal {
+    fn name(&self) -> &'static str { "Kruskal" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let cw = c.cw;
+        let idx = |x: usize, y: usize| y * cw + x;
+        let mut edges = Vec::new();
+        for y in 0..c.ch {
+            for x in 0..c.cw {
+                if x + 1 < c.cw { edges.push(((x, y), (x + 1, y))); }
+                if y + 1 < c.ch { edges.push(((x, y), (x, y + 1))); }
+            }
+        }
+        edges.shuffle(rng);
+
+        let mut parent: Vec<usize> = (0..c.cw * c.ch).collect();
+        for (a, b) in edges {
+            let ra = find(&mut parent, idx(a.0, a.1));
+            let rb = find(&mut parent, idx(b.0, b.1));
+            if ra != rb {
+                parent[ra] = rb;
+                c.link(a, b);
+            }
+        }
+    }
+}
+
+/// Wilson: caminatas aleatorias con borrado de ciclos (laberinto uniforme).
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn name(&self) -> &'static str { "Wilson" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let (cw, n) = (c.cw, c.cw * c.ch);
+        let cell = |i: usize| (i % cw, i / cw);
+        let mut in_maze = BitSet::new(n);
+        let mut next = vec![usize::MAX; n];
+
+        let root = rng.gen_range(0..n);
+        in_maze.set(root, true);
+        c.open(root % c.cw, root / c.cw);
+
+        for start in 0..n {
+            if in_maze.get(start) { continue; }
+            // Caminata: `next` guarda la última salida de cada celda (borra ciclos)
+            let mut cur = start;
+            while !in_maze.get(cur) {
+                let (x, y) = cell(cur);
+                let ns = c.neighbors(x, y);
+                let (nx, ny) = ns[rng.gen_range(0..ns.len())];
+                next[cur] = ny * c.cw + nx;
+                cur = next[cur];
+            }
+            // Tallar el camino sin ciclos
+            let mut cur = start;
+            while !in_maze.get(cur) {
+                in_maze.set(cur, true);
+                c.link(cell(cur), cell(next[cur]));
+                cur = next[cur];
+            }
+        }
+    }
+}
+
+/// Eller: fila por fila, con conjuntos por fila (memoria O(ancho)).
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn name(&self) -> &'static str { "Eller" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let w = c.cw;
+        let mut sets: Vec<usize> = (0..w).collect();
+        let mut next_set = w;
+
+        for y in 0..c.ch {
+            let last = y + 1 == c.ch;
+            for x in 0..w { c.open(x, y); }
+
+            // Unir horizontalmente (en la última fila, todo lo que esté separado)
+            for x in 0..w.saturating_sub(1) {
+                if sets[x] != sets[x + 1] && (last || rng.gen_bool(0.5)) {
+                    c.link((x, y), (x + 1, y));
+                    let (from, to) = (sets[x + 1], sets[x]);
+                    for s in sets.iter_mut() { if *s == from { *s = to; } }
+                }
+            }
+            if last { break; }
+
+            // Bajar: al menos una celda por conjunto
+            let mut below = vec![usize::MAX; w];
+            let mut xs: Vec<usize> = (0..w).collect();
+            xs.shuffle(rng);
+            let mut has_down = std::collections::HashSet::new();
+            for &x in &xs {
+                if !has_down.contains(&sets[x]) || rng.gen_bool(0.3) {
+                    has_down.insert(sets[x]);
+                    c.link((x, y), (x, y + 1));
+                    below[x] = sets[x];
+                }
+            }
+            for s in below.iter_mut() {
+                if *s == usize::MAX { *s = next_set; next_set += 1; }
+            }
+            sets = below;
+        }
+    }
+}
+
+/// Growing-tree con sesgo configurable de elección.
+pub struct GrowingTree(pub TreeBias);
+
+impl MazeGenerator for GrowingTree {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            TreeBias::Newest   => "Growing tree (nuevo)",
+            TreeBias::Oldest   => "Growing tree (viejo)",
+            TreeBias::Random   => "Growing tree (azar)",
+            TreeBias::Middle   => "Growing tree (medio)",
+            TreeBias::Mixed(_) => "Growing tree (mixto)",
+        }
+    }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        let mut visited = BitSet::new(c.cw * c.ch);
+        let start = (rng.gen_range(0..c.cw), rng.gen_range(0..c.ch));
+        visited.set(c.idx(start.0, start.1), true);
+        c.open(start.0, start.1);
+        // Sacar una celda es O(1): la primera sale por delante y cualquier
+        // otra se reemplaza por la última. Newest y Oldest quedan exactos;
+        // en Random da igual, y Middle/Mixed se desordenan un poco.
+        let mut active = VecDeque::from([start]);
+
+        while !active.is_empty() {
+            let k = match self.0 {
+                TreeBias::Newest   => active.len() - 1,
+                TreeBias::Oldest   => 0,
+                TreeBias::Random   => rng.gen_range(0..active.len()),
+                TreeBias::Middle   => active.len() / 2,
+                TreeBias::Mixed(p) => {
+                    if rng.gen_range(0..100) < p as u32 { active.len() - 1 }
+                    else { rng.gen_range(0..active.len()) }
+                }
+            };
+            let (x, y) = active[k];
+            let free: Vec<_> = c.neighbors(x, y).into_iter().filter(|&(nx, ny)| !visited.get(c.idx(nx, ny))).collect();
+            match free.choose(rng) {
+                Some(&(nx, ny)) => {
+                    visited.set(c.idx(nx, ny), true);
+                    c.link((x, y), (nx, ny));
+                    active.push_back((nx, ny));
+                }
+                None if k == 0 => { active.pop_front(); }
+                None => { active.swap_remove_back(k); }
+            }
+        }
+    }
+}
+
+/// Árbol binario: cada celda abre al norte o al este (diagonal muy marcada).
+pub struct BinaryTree;
+
+impl MazeGenerator for BinaryTree {
+    fn name(&self) -> &'static str { "Binary tree" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        for y in 0..c.ch {
+            for x in 0..c.cw {
+                c.open(x, y);
+                let north = y > 0;
+                let east  = x + 1 < c.cw;
+                match (north, east) {
+                    (true, true)   => if rng.gen_bool(0.5) { c.link((x, y), (x, y - 1)) } else { c.link((x, y), (x + 1, y)) },
+                    (true, false)  => c.link((x, y), (x, y - 1)),
+                    (false, true)  => c.link((x, y), (x + 1, y)),
+                    (false, false) => {}
+                }
+            }
+        }
+    }
+}
+
+/// División recursiva: parte de un espacio abierto y agrega muros con un hueco.
+pub struct Division;
+
+impl MazeGenerator for Division {
+    fn name(&self) -> &'static str { "División recursiva" }
+
+    fn carve(&self, c: &mut Carver, rng: &mut dyn RngCore) {
+        for y in 0..c.ch {
+            for x in 0..c.cw {
+                if x + 1 < c.cw { c.link((x, y), (x + 1, y)); }
+                if y + 1 < c.ch { c.link((x, y), (x, y + 1)); }
+            }
+        }
+
+        // Pila explícita de regiones (x, y, w, h) en celdas
+        let mut regions = vec![(0, 0, c.cw, c.ch)];
+        while let Some((x, y, w, h)) = regions.pop() {
+            if w < 2 || h < 2 { continue; }
+            let horizontal = if w < h { true } else if h < w { false } else { rng.gen_bool(0.5) };
+
+            if horizontal {
+                // muro entre las filas wy y wy+1, con un hueco en gx
+                let wy = y + rng.gen_range(0..h - 1);
+                let gx = x + rng.gen_range(0..w);
+                for cx in x..x + w {
+                    if cx != gx { c.unlink((cx, wy), (cx, wy + 1)); }
+                }
+                regions.push((x, y, w, wy - y + 1));
+                regions.push((x, wy + 1, w, y + h - wy - 1));
+            } else {
+                let wx = x + rng.gen_range(0..w - 1);
+                let gy = y + rng.gen_range(0..h);
+                for cy in y..y + h {
+                    if cy != gy { c.unlink((wx, cy), (wx + 1, cy)); }
+                }
+                regions.push((x, y, wx - x + 1, h));
+                regions.push((wx + 1, y, x + w - wx - 1, h));
+            }
+        }
+    }
+}
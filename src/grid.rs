@@ -0,0 +1,93 @@
+// src/grid.rs
+use std::ops::{Index, IndexMut};
+use crate::tile::Tile;
+
+/// Mapa de tiles en un solo `Vec` (fila por fila). Se indexa con `(i, j)` =
+/// (columna, fila); `get` devuelve `None` fuera de rango.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Grid {
+    w: usize,
+    h: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Grid {
+    pub fn new(w: usize, h: usize, fill: Tile) -> Self {
+        Self { w, h, tiles: vec![fill; w * h] }
+    }
+
+    /// Desde filas (deben tener todas el mismo ancho).
+    pub fn from_rows(rows: Vec<Vec<Tile>>) -> Self {
+        let h = rows.len();
+        let w = rows.first().map_or(0, |r| r.len());
+        debug_assert!(rows.iter().all(|r| r.len() == w), "filas de distinto ancho");
+        Self { w, h, tiles: rows.into_iter().flatten().collect() }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    #[inline]
+    pub fn in_bounds(&self, i: usize, j: usize) -> bool {
+        i < self.w && j < self.h
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> Option<Tile> {
+        self.in_bounds(i, j).then(|| self.tiles[j * self.w + i])
+    }
+
+    /// Como `get`, con coordenadas que pueden ser negativas (vecinos).
+    #[inline]
+    pub fn get_signed(&self, i: isize, j: isize) -> Option<Tile> {
+        if i < 0 || j < 0 { return None; }
+        self.get(i as usize, j as usize)
+    }
+
+    /// Tile bajo un punto del mundo (`block_size` unidades por tile).
+    #[inline]
+    pub fn at_world(&self, x: f32, y: f32, block_size: usize) -> Option<Tile> {
+        if x < 0.0 || y < 0.0 { return None; }
+        self.get(x as usize / block_size, y as usize / block_size)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[Tile]> {
+        self.tiles.chunks(self.w.max(1))
+    }
+
+    /// Todas las casillas con su posición, en orden de lectura.
+    pub fn cells(&self) -> impl Iterator<Item = ((usize, usize), Tile)> + '_ {
+        self.tiles.iter().enumerate().map(|(k, &t)| ((k % self.w, k / self.w), t))
+    }
+
+    /// Primera casilla con ese tile (en orden de lectura).
+    pub fn find(&self, tile: Tile) -> Option<(usize, usize)> {
+        self.cells().find(|&(_, t)| t == tile).map(|(c, _)| c)
+    }
+
+}
+
+impl Index<(usize, usize)> for Grid {
+    type Output = Tile;
+
+    #[inline]
+    fn index(&self, (i, j): (usize, usize)) -> &Tile {
+        assert!(self.in_bounds(i, j), "tile ({i}, {j}) fuera del grid {}x{}", self.w, self.h);
+        &self.tiles[j * self.w + i]
+    }
+}
+
+impl IndexMut<(usize, usize)> for Grid {
+    #[inline]
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Tile {
+        assert!(self.in_bounds(i, j), "tile ({i}, {j}) fuera del grid {}x{}", self.w, self.h);
+        &mut self.tiles[j * self.w + i]
+    }
+}
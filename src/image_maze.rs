@@ -0,0 +1,102 @@
+// src/image_maze.rs
+use std::fmt;
+use image::ImageError;
+use crate::grid::Grid;
+use crate::maze::{validate_maze, LoadError, Maze};
+use crate::tile::Tile;
+
+/// Qué tile corresponde a cada color (RGB exacto; el alpha se ignora).
+/// Un píxel = un tile.
+#[derive(Clone)]
+pub struct Legend {
+    entries: Vec<([u8; 3], Tile)>,
+}
+
+impl Default for Legend {
+    /// Negro = muro, gris = piedra, blanco = suelo, azul = inicio, verde = meta,
+    /// cian = espejo. Los portales no tienen color por defecto.
+    fn default() -> Self {
+        Self {
+            entries: vec![
+                ([0, 0, 0], Tile::Wall),
+                ([128, 128, 128], Tile::Stone),
+                ([255, 255, 255], Tile::Floor),
+                ([0, 0, 255], Tile::Start),
+                ([0, 255, 0], Tile::Goal),
+                ([0, 255, 255], Tile::Mirror),
+            ],
+        }
+    }
+}
+
+impl Legend {
+    /// Asigna (o reasigna) un color.
+    pub fn set(&mut self, rgb: [u8; 3], tile: Tile) {
+        self.entries.retain(|&(c, _)| c != rgb);
+        self.entries.push((rgb, tile));
+    }
+
+    pub fn tile(&self, rgb: [u8; 3]) -> Option<Tile> {
+        self.entries.iter().find(|&&(c, _)| c == rgb).map(|&(_, t)| t)
+    }
+}
+
+/// Color en hexadecimal, `RRGGBB` o `#RRGGBB`.
+pub fn parse_hex_color(s: &str) -> Option<[u8; 3]> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 { return None; }
+    let v = u32::from_str_radix(hex, 16).ok()?;
+    Some([(v >> 16) as u8, (v >> 8) as u8, v as u8])
+}
+
+#[derive(Debug)]
+pub enum ImageMazeError {
+    Image(String, ImageError),
+    /// Primer píxel sin color en la leyenda y cuántos hay en total.
+    Unmatched { x: u32, y: u32, rgb: [u8; 3], count: usize },
+    Maze(LoadError),
+}
+
+impl fmt::Display for ImageMazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageMazeError::Image(path, e) => write!(f, "{path}: {e}"),
+            ImageMazeError::Unmatched { x, y, rgb: [r, g, b], count } => write!(
+                f,
+                "píxel ({x}, {y}): el color #{r:02X}{g:02X}{b:02X} no está en la leyenda \
+                 ({count} píxel(es) sin correspondencia en total)"
+            ),
+            ImageMazeError::Maze(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageMazeError {}
+
+/// Carga una imagen como laberinto usando `legend` y la valida igual que un
+/// archivo de texto (la línea/columna de los errores es fila/columna + 1).
+pub fn load_image_maze(path: &str, legend: &Legend, require_solvable: bool) -> Result<Maze, ImageMazeError> {
+    let img = image::open(path)
+        .map_err(|e| ImageMazeError::Image(path.to_string(), e))?
+        .to_rgb8();
+    let (w, h) = img.dimensions();
+
+    let mut maze: Maze = Grid::new(w as usize, h as usize, Tile::Wall);
+    let mut first_bad = None;
+    let mut bad = 0;
+    for (x, y, p) in img.enumerate_pixels() {
+        match legend.tile(p.0) {
+            Some(t) => maze[(x as usize, y as usize)] = t,
+            None => {
+                bad += 1;
+                first_bad.get_or_insert((x, y, p.0));
+            }
+        }
+    }
+    if let Some((x, y, rgb)) = first_bad {
+        return Err(ImageMazeError::Unmatched { x, y, rgb, count: bad });
+    }
+
+    validate_maze(&maze, require_solvable).map_err(ImageMazeError::Maze)?;
+    Ok(maze)
+}
@@ -0,0 +1,573 @@
+// src/level.rs
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use raylib::color::Color;
+use crate::binlevel::{self, BinError};
+use crate::decals::{parse_decal_line, DecalSpec};
+use crate::dungeon::DungeonOptions;
+use crate::generators::{Algorithm, TreeBias};
+use crate::image_maze::{load_image_maze, parse_hex_color, ImageMazeError, Legend};
+use crate::maze::{parse_storeys, LoadError, Maze, Placement};
+use crate::painters::{Painter, WallPaint};
+use crate::tile::Tile;
+use crate::particles::{self, EmitterParams};
+use crate::sprites::SpriteAnchor;
+use crate::storeys::Link;
+
+/// Archivo de nivel (`.lvl`): directivas `@clave valores...` más, para los
+/// niveles hechos a mano, el grid de caracteres (las líneas sin '@').
+/// Coordenadas en tiles (admiten decimales), ángulos en grados, colores `r g b`.
+///
+/// ```text
+/// @name Cripta
+/// @author Ana
+/// @par 60                        segundos
+/// @angle 90                      orientación inicial
+/// @texture A assets/stone.jpg    textura por carácter de muro
+/// @sky 20 20 30
+/// @floor 30 25 20
+/// @fog 10 10 15 8                color y distancia (tiles)
+/// @ambient 0.5
+/// @light 3.5 1.5 4 255 180 90    x y radio r g b
+/// @sprite 5.5 3.5 crate 0.2 0.4  x y textura [radio [tamaño [floor|center|ceiling [z]]]]
+/// @emitter 3.5 1.5 0.8 smoke     x y z dust|sparkles|smoke
+/// @decal 4 2 N arrow             (ver `decals::parse_decal_line`)
+/// @decal 6 1 S text "SALIDA ->"  letrero
+/// ```
+///
+/// En vez de escribir el grid, se puede pintar en una imagen (un píxel por
+/// tile) y cambiar los colores de la leyenda (ver `image_maze::Legend`):
+///
+/// ```text
+/// @image cripta.png
+/// @legend FF0000 1               color RRGGBB -> tile ("space" = suelo)
+/// ```
+///
+/// Un nivel procedural no trae grid y en su lugar declara:
+///
+/// ```text
+/// @generator wilson 16 12        algoritmo, ancho y alto en celdas
+/// @seed 1234                     opcional; si falta se usa la del menú
+/// @braid 50
+/// @mirrors 4
+/// @portals 1
+/// @placement diameter            fixed | farthest | diameter | random <celdas>
+/// @difficulty 40                 opcional; puntaje buscado (0..100, ver `metrics`)
+/// @walls noise 6 # A V           materiales de muro (ver abajo)
+/// ```
+///
+/// Con `@difficulty` el tamaño de `@generator` pasa a ser el máximo: se usa
+/// el tamaño (con las mismas proporciones) cuyo puntaje quede más cerca.
+///
+/// `@walls <pintor> [parámetro] [materiales...]` reparte materiales de muro
+/// (caracteres de tile, por defecto los de cada pintor; ver `painters`):
+///
+/// ```text
+/// @walls stripes                 franjas fijas (lo de siempre)
+/// @walls noise 6 # A V           manchas; tiles entre puntos del ruido
+/// @walls voronoi 8 # A D R       zonas alrededor de 8 puntos
+/// @walls depth # D V N           más oscuro cuanto más lejos del inicio
+/// @walls rooms # A R V D         un material por sala (solo con `rooms`)
+/// ```
+///
+/// Con `@generator rooms W H` se generan salas unidas por pasillos (ver
+/// `dungeon::make_dungeon`) en un mapa del mismo tamaño; `@braid` pasa a ser
+/// el % de conexiones extra y `@placement` no se usa (inicio y meta van en
+/// las salas más alejadas):
+///
+/// ```text
+/// @rooms 10 3 7                  cuántas salas como mucho, lado mínimo y máximo
+/// @roomlight 3 255 200 120       luz en el centro de cada sala: radio r g b
+/// ```
+///
+/// Varios pisos: `@storey` empieza el piso de arriba. En un nivel a mano va
+/// seguido de su grid ('<' sube, '>' baja, 'E' ascensor); las luces, sprites,
+/// emisores y calcomanías que vienen después son de ese piso. Un nivel
+/// procedural apila pisos generados con
+///
+/// ```text
+/// @storeys 3 elevator            cuántos pisos y cómo se unen (stairs | elevator)
+/// ```
+#[derive(Clone)]
+pub struct LevelDef {
+    pub name: String,
+    pub author: Option<String>,
+    pub par: Option<f32>,
+    pub start_angle: Option<f32>, // radianes
+    pub textures: Vec<(Tile, String)>,
+    pub sky: Option<Color>,
+    pub floor: Option<Color>,
+    pub fog: Option<(Color, Option<f32>)>,
+    pub ambient: Option<f32>,
+    pub lights: Vec<LightSpec>,
+    pub sprites: Vec<SpriteSpec>,
+    pub emitters: Vec<EmitterSpec>,
+    pub decals: Vec<DecalSpec>,
+    pub room_light: Option<(f32, Color)>, // radio (tiles) y color, solo con `rooms`
+    pub source: LevelSource,
+    pub id: String,   // nombre del archivo (clave de récords)
+    pub dir: PathBuf, // carpeta del archivo, para rutas relativas
+}
+
+/// De dónde sale el grid del nivel.
+#[derive(Clone)]
+pub enum LevelSource {
+    Grid(Vec<Maze>), // un grid por piso
+    Procedural(ProcSpec),
+}
+
+/// Qué genera un nivel procedural: un laberinto o salas con pasillos.
+#[derive(Clone, Copy, Debug)]
+pub enum Generator {
+    Maze(Algorithm),
+    Rooms(DungeonOptions),
+}
+
+impl Generator {
+    pub fn name(self) -> &'static str {
+        match self {
+            Generator::Maze(algo) => algo.name(),
+            Generator::Rooms(_) => "Salas",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ProcSpec {
+    pub generator: Generator,
+    pub cells: (usize, usize),
+    pub seed: Option<u64>,
+    pub braid: u8,
+    pub mirrors: usize,
+    pub portal_pairs: usize,
+    pub placement: Placement,
+    pub storeys: usize,
+    pub link: Link,
+    pub difficulty: Option<f32>,
+    pub walls: WallPaint,
+}
+
+#[derive(Clone)]
+pub struct LightSpec {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: Color,
+    pub storey: usize,
+}
+
+#[derive(Clone)]
+pub struct SpriteSpec {
+    pub x: f32,
+    pub y: f32,
+    pub texture: String,
+    pub radius: f32, // colisión (0 = atravesable)
+    pub size: f32,
+    pub anchor: SpriteAnchor,
+    pub z: f32,
+    pub storey: usize,
+}
+
+#[derive(Clone)]
+pub struct EmitterSpec {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub params: EmitterParams,
+    pub storey: usize,
+}
+
+impl LevelDef {
+    /// Nivel vacío (sin grid ni directivas); el id es el nombre.
+    pub fn new(name: &str, dir: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            author: None,
+            par: None,
+            start_angle: None,
+            textures: Vec::new(),
+            sky: None,
+            floor: None,
+            fog: None,
+            ambient: None,
+            lights: Vec::new(),
+            sprites: Vec::new(),
+            emitters: Vec::new(),
+            decals: Vec::new(),
+            room_light: None,
+            source: LevelSource::Grid(Vec::new()),
+            id: name.to_string(),
+            dir,
+        }
+    }
+
+    /// Ruta de un recurso: relativa a la carpeta del nivel si existe ahí.
+    pub fn resolve_path(&self, name: &str) -> String {
+        let local = self.dir.join(name);
+        if local.exists() { local.to_string_lossy().into_owned() } else { name.to_string() }
+    }
+
+    /// Cuántos pisos tiene el nivel.
+    pub fn storeys(&self) -> usize {
+        match &self.source {
+            LevelSource::Grid(storeys) => storeys.len(),
+            LevelSource::Procedural(p) => p.storeys,
+        }
+    }
+
+    /// Nombre del generador, o "a mano" si el grid viene en el archivo.
+    pub fn kind(&self) -> &'static str {
+        match &self.source {
+            LevelSource::Grid(_) => "a mano",
+            LevelSource::Procedural(p) => p.generator.name(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(String, std::io::Error),
+    Directive { line: usize, msg: String },
+    Maze(LoadError),
+    Image(ImageMazeError),
+    Binary(BinError),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(path, e) => write!(f, "{path}: {e}"),
+            LevelError::Directive { line, msg } => write!(f, "línea {line}: {msg}"),
+            LevelError::Maze(e) => write!(f, "{e}"),
+            LevelError::Image(e) => write!(f, "{e}"),
+            LevelError::Binary(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<LoadError> for LevelError {
+    fn from(e: LoadError) -> Self {
+        LevelError::Maze(e)
+    }
+}
+
+/// Carga un `.lvl` o un `.lvlb` (ver `binlevel`); una imagen suelta (`.png`)
+/// se carga como grid con la leyenda por defecto.
+pub fn load_level(path: &Path) -> Result<LevelDef, LevelError> {
+    if path.extension().is_some_and(|x| x.eq_ignore_ascii_case("png")) {
+        let name = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        return parse_level(&format!("@image {name}"), &name, dir);
+    }
+    let io = |e| LevelError::Io(path.display().to_string(), e);
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let mut def = if path.extension().is_some_and(|x| x == binlevel::EXTENSION) {
+        binlevel::from_bytes(&fs::read(path).map_err(io)?, &stem, dir).map_err(LevelError::Binary)?
+    } else {
+        parse_level(&fs::read_to_string(path).map_err(io)?, &stem, dir)?
+    };
+    def.id = path.file_name().map(|s| s.to_string_lossy().into_owned()).unwrap_or(stem);
+    Ok(def)
+}
+
+/// Archivos `.lvl` y `.lvlb` de una carpeta, ordenados por nombre (las imágenes no se
+/// listan: suelen ser el grid de algún `.lvl`). Si `path` es un archivo, lo
+/// devuelve tal cual; si no existe, no hay niveles.
+pub fn discover_levels(path: &Path) -> Vec<PathBuf> {
+    if path.is_file() {
+        return vec![path.to_path_buf()];
+    }
+    let Ok(entries) = fs::read_dir(path) else { return Vec::new() };
+    let mut out: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().is_some_and(|x| x == "lvl" || x == binlevel::EXTENSION))
+        .collect();
+    out.sort();
+    out
+}
+
+/// Interpreta el texto de un nivel; `default_name` se usa si no hay `@name`.
+pub fn parse_level(text: &str, default_name: &str, dir: PathBuf) -> Result<LevelDef, LevelError> {
+    let mut def = LevelDef::new(default_name, dir);
+    let mut proc: Option<ProcSpec> = None;
+    let mut generator_line = 0;
+    let mut has_grid = false;
+    let mut image: Option<(usize, String)> = None;
+    let mut legend = Legend::default();
+    let mut storey = 0;
+    let mut storey_line = 0;
+
+    for (n, raw) in text.lines().enumerate() {
+        let line = n + 1;
+        let Some(directive) = raw.strip_prefix('@') else {
+            has_grid |= !raw.is_empty();
+            continue;
+        };
+        let err = |msg: String| LevelError::Directive { line, msg };
+        let (key, rest) = directive.split_once(char::is_whitespace).unwrap_or((directive, ""));
+        let rest = rest.trim();
+        let args: Vec<&str> = rest.split_whitespace().collect();
+
+        match key {
+            "name"    => def.name = rest.to_string(),
+            "author"  => def.author = Some(rest.to_string()),
+            "par"     => def.par = Some(num(&args, 0).map_err(err)?),
+            "angle"   => def.start_angle = Some(num::<f32>(&args, 0).map_err(err)?.to_radians()),
+            "texture" => {
+                let [tile, path] = args[..] else { return Err(err("uso: @texture <c> <ruta>".into())) };
+                def.textures.push((tile_arg(tile).map_err(err)?, path.to_string()));
+            }
+            "sky"     => def.sky = Some(color(&args, 0).map_err(err)?),
+            "floor"   => def.floor = Some(color(&args, 0).map_err(err)?),
+            "fog"     => {
+                let dist = if args.len() > 3 { Some(num(&args, 3).map_err(err)?) } else { None };
+                def.fog = Some((color(&args, 0).map_err(err)?, dist));
+            }
+            "ambient" => def.ambient = Some(num(&args, 0).map_err(err)?),
+            "light"   => def.lights.push(LightSpec {
+                x: num(&args, 0).map_err(err)?,
+                y: num(&args, 1).map_err(err)?,
+                radius: num(&args, 2).map_err(err)?,
+                color: color(&args, 3).map_err(err)?,
+                storey,
+            }),
+            "sprite"  => {
+                let texture = args.get(2).ok_or_else(|| err("uso: @sprite x y textura [radio [tamaño [ancla [z]]]]".into()))?;
+                let anchor = match args.get(5).copied() {
+                    None | Some("floor") => SpriteAnchor::Floor,
+                    Some("center")       => SpriteAnchor::Center,
+                    Some("ceiling")      => SpriteAnchor::Ceiling,
+                    Some(a) => return Err(err(format!("ancla inválida '{a}' (floor/center/ceiling)"))),
+                };
+                let default_z = if anchor == SpriteAnchor::Ceiling { 1.0 } else { 0.0 };
+                def.sprites.push(SpriteSpec {
+                    x: num(&args, 0).map_err(err)?,
+                    y: num(&args, 1).map_err(err)?,
+                    texture: texture.to_string(),
+                    radius: opt_num(&args, 3, 0.0).map_err(err)?,
+                    size: opt_num(&args, 4, 0.4).map_err(err)?,
+                    anchor,
+                    z: opt_num(&args, 6, default_z).map_err(err)?,
+                    storey,
+                });
+            }
+            "emitter" => {
+                let params = match args.get(3).copied() {
+                    Some("dust")     => particles::dust(),
+                    Some("sparkles") => particles::sparkles(),
+                    Some("smoke")    => particles::smoke(),
+                    _ => return Err(err("uso: @emitter x y z dust|sparkles|smoke".into())),
+                };
+                def.emitters.push(EmitterSpec {
+                    x: num(&args, 0).map_err(err)?,
+                    y: num(&args, 1).map_err(err)?,
+                    z: num(&args, 2).map_err(err)?,
+                    params,
+                    storey,
+                });
+            }
+            "decal" => def.decals.push(DecalSpec { storey, ..parse_decal_line(raw).map_err(err)? }),
+            "storey" => {
+                storey += 1;
+                storey_line = line;
+            }
+            "image" => {
+                if rest.is_empty() { return Err(err("uso: @image <ruta>".into())); }
+                image = Some((line, rest.to_string()));
+            }
+            "legend" => {
+                let [hex, tile] = args[..] else { return Err(err("uso: @legend RRGGBB <tile>".into())) };
+                let rgb = parse_hex_color(hex).ok_or_else(|| err(format!("color inválido '{hex}'")))?;
+                legend.set(rgb, tile_arg(tile).map_err(err)?);
+            }
+            "generator" => {
+                let name = args.first().copied().unwrap_or("");
+                let generator = if name.eq_ignore_ascii_case("rooms") {
+                    Generator::Rooms(DungeonOptions::default())
+                } else {
+                    Generator::Maze(parse_algorithm(name).ok_or_else(|| err(format!("generador desconocido '{name}'")))?)
+                };
+                let cells = (num(&args, 1).map_err(err)?, num(&args, 2).map_err(err)?);
+                if cells.0 == 0 || cells.1 == 0 || cells == (1, 1) {
+                    return Err(err("el laberinto necesita al menos 2 celdas (inicio y meta)".into()));
+                }
+                generator_line = line;
+                proc = Some(ProcSpec {
+                    generator,
+                    cells,
+                    seed: None,
+                    braid: 0,
+                    mirrors: 0,
+                    portal_pairs: 0,
+                    placement: Placement::Fixed,
+                    storeys: 1,
+                    link: Link::Stairs,
+                    difficulty: None,
+                    walls: WallPaint::default(),
+                });
+            }
+            "seed"      => { let v = num(&args, 0).map_err(err)?;        generator_of(&mut proc, key, line)?.seed = Some(v); }
+            "braid"     => {
+                let v = num(&args, 0).map_err(err)?;
+                let p = generator_of(&mut proc, key, line)?;
+                match &mut p.generator {
+                    Generator::Rooms(opts) => opts.extra = v,
+                    Generator::Maze(_) => p.braid = v,
+                }
+            }
+            "mirrors"   => { let v = num(&args, 0).map_err(err)?;        generator_of(&mut proc, key, line)?.mirrors = v; }
+            "portals"   => { let v = num(&args, 0).map_err(err)?;        generator_of(&mut proc, key, line)?.portal_pairs = v; }
+            "placement" => { let v = parse_placement(&args).map_err(err)?; generator_of(&mut proc, key, line)?.placement = v; }
+            "difficulty" => {
+                let v: f32 = num(&args, 0).map_err(err)?;
+                if !(v > 0.0 && v < 100.0) { return Err(err("la dificultad va de 0 a 100".into())); }
+                generator_of(&mut proc, key, line)?.difficulty = Some(v);
+            }
+            "walls" => {
+                let walls = parse_walls(&args).map_err(err)?;
+                let p = generator_of(&mut proc, key, line)?;
+                if walls.painter == Painter::Rooms && !matches!(p.generator, Generator::Rooms(_)) {
+                    return Err(err("@walls rooms requiere @generator rooms".into()));
+                }
+                p.walls = walls;
+            }
+            "storeys" => {
+                let n: usize = num(&args, 0).map_err(err)?;
+                let link = match args.get(1).copied() {
+                    None | Some("stairs") => Link::Stairs,
+                    Some("elevator")      => Link::Elevator,
+                    Some(l) => return Err(err(format!("unión inválida '{l}' (stairs/elevator)"))),
+                };
+                if n == 0 { return Err(err("un nivel necesita al menos un piso".into())); }
+                let p = generator_of(&mut proc, key, line)?;
+                (p.storeys, p.link) = (n, link);
+            }
+            "rooms" => {
+                let d = DungeonOptions::default();
+                let v = (num(&args, 0).map_err(err)?, opt_num(&args, 1, d.min_size).map_err(err)?, opt_num(&args, 2, d.max_size).map_err(err)?);
+                let Generator::Rooms(opts) = &mut generator_of(&mut proc, key, line)?.generator else {
+                    return Err(err("@rooms requiere @generator rooms".into()));
+                };
+                (opts.max_rooms, opts.min_size, opts.max_size) = v;
+            }
+            "roomlight" => {
+                if !proc.as_ref().is_some_and(|p| matches!(p.generator, Generator::Rooms(_))) {
+                    return Err(err("@roomlight requiere @generator rooms".into()));
+                }
+                def.room_light = Some((num(&args, 0).map_err(err)?, color(&args, 1).map_err(err)?));
+            }
+            _ => return Err(err(format!("directiva desconocida '@{key}'"))),
+        }
+    }
+
+    let conflict = |line: usize, what: &str| LevelError::Directive {
+        line,
+        msg: format!("un nivel no puede tener {what} a la vez"),
+    };
+    def.source = match (proc, image, has_grid) {
+        (Some(_), Some(_), _)          => return Err(conflict(generator_line, "@generator e @image")),
+        (Some(_), None, true)          => return Err(conflict(generator_line, "@generator y grid")),
+        (None, Some((l, _)), true)     => return Err(conflict(l, "@image y grid")),
+        (Some(p), None, false)         => LevelSource::Procedural(p),
+        (None, Some((_, path)), false) => {
+            let path = def.resolve_path(&path);
+            LevelSource::Grid(vec![load_image_maze(&path, &legend, true).map_err(LevelError::Image)?])
+        }
+        (None, None, _) => LevelSource::Grid(parse_storeys(text, true)?),
+    };
+    if storey >= def.storeys() {
+        let n = def.storeys();
+        return Err(LevelError::Directive { line: storey_line, msg: format!("el nivel solo tiene {n} piso(s)") });
+    }
+    Ok(def)
+}
+
+/// Las opciones procedurales solo tienen sentido después de `@generator`.
+fn generator_of<'a>(proc: &'a mut Option<ProcSpec>, key: &str, line: usize) -> Result<&'a mut ProcSpec, LevelError> {
+    proc.as_mut().ok_or_else(|| LevelError::Directive { line, msg: format!("@{key} requiere @generator antes") })
+}
+
+/// Nombres aceptados por `@generator`.
+pub fn parse_algorithm(name: &str) -> Option<Algorithm> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "dfs" | "backtracker" => Algorithm::Backtracker,
+        "prim"        => Algorithm::Prim,
+        "kruskal"     => Algorithm::Kruskal,
+        "wilson"      => Algorithm::Wilson,
+        "eller"       => Algorithm::Eller,
+        "binary"      => Algorithm::BinaryTree,
+        "division"    => Algorithm::Division,
+        "tree-newest" => Algorithm::GrowingTree(TreeBias::Newest),
+        "tree-oldest" => Algorithm::GrowingTree(TreeBias::Oldest),
+        "tree-random" => Algorithm::GrowingTree(TreeBias::Random),
+        "tree-middle" => Algorithm::GrowingTree(TreeBias::Middle),
+        other => {
+            // tree-mixed-NN: NN% Newest, el resto Random
+            let pct = other.strip_prefix("tree-mixed-")?.parse().ok()?;
+            Algorithm::GrowingTree(TreeBias::Mixed(pct))
+        }
+    })
+}
+
+fn parse_placement(args: &[&str]) -> Result<Placement, String> {
+    match args.first().copied() {
+        Some("fixed")    => Ok(Placement::Fixed),
+        Some("farthest") => Ok(Placement::FarthestFromStart),
+        Some("diameter") => Ok(Placement::Diameter),
+        Some("random")   => Ok(Placement::RandomMinPath(num(args, 1)?)),
+        _ => Err("uso: @placement fixed|farthest|diameter|random <celdas>".into()),
+    }
+}
+
+/// `@walls <pintor> [parámetro] [materiales...]`.
+fn parse_walls(args: &[&str]) -> Result<WallPaint, String> {
+    let usage = || "uso: @walls stripes|noise|voronoi|depth|rooms [parámetro] [materiales...]".to_string();
+    let (name, mut rest) = args.split_first().ok_or_else(usage)?;
+    let mut param = |default: f32| match rest.first().and_then(|a| a.parse::<f32>().ok()) {
+        Some(v) => { rest = &rest[1..]; v }
+        None => default,
+    };
+    let painter = match *name {
+        "stripes" => Painter::Stripes,
+        "noise"   => Painter::Noise { scale: param(6.0) },
+        "voronoi" => Painter::Voronoi { zones: param(8.0) as usize },
+        "depth"   => Painter::Depth,
+        "rooms"   => Painter::Rooms,
+        _ => return Err(usage()),
+    };
+    let mut palette = Vec::new();
+    for a in rest {
+        let tile = tile_arg(a)?;
+        if !tile.is_material() { return Err(format!("'{a}' no es un material de muro")); }
+        palette.push(tile);
+    }
+    if palette.is_empty() { palette = painter.default_palette(); }
+    Ok(WallPaint { painter, palette })
+}
+
+fn num<T: std::str::FromStr>(args: &[&str], k: usize) -> Result<T, String> {
+    let s = args.get(k).ok_or_else(|| format!("falta el argumento {}", k + 1))?;
+    s.parse().map_err(|_| format!("número inválido '{s}'"))
+}
+
+fn opt_num<T: std::str::FromStr>(args: &[&str], k: usize, default: T) -> Result<T, String> {
+    if k < args.len() { num(args, k) } else { Ok(default) }
+}
+
+fn color(args: &[&str], k: usize) -> Result<Color, String> {
+    Ok(Color::new(num(args, k)?, num(args, k + 1)?, num(args, k + 2)?, 255))
+}
+
+/// Tile por su carácter de archivo ("space" = suelo).
+fn tile_arg(s: &str) -> Result<Tile, String> {
+    if s == "space" { return Ok(Tile::Floor); }
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Tile::from_char(c).ok_or_else(|| format!("tile desconocido '{c}'")),
+        _ => Err(format!("'{s}' no es un solo carácter")),
+    }
+}
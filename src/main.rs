@@ -11,21 +11,60 @@ mod texture;
 mod textures;
 mod minimap;
 mod sprites; // sprites 2D en el mundo (billboard)
+mod particles;
+mod decals;
+mod portals;
+mod generators;
+mod solver;
+mod level;
+mod records;
+mod image_maze;
+mod export;
+mod tile;
+mod grid;
+mod dungeon;
+mod storeys;
+mod metrics;
+mod painters;
+mod endless;
+mod daily;
+mod bytes;
+mod binlevel;
+mod sharecode;
 
 use minimap::{draw_minimap, MiniMapOpts, Corner};
 
 use crate::framebuffer::Framebuffer;
-use crate::maze::{load_maze, make_maze, Maze};
+use crate::maze::{make_maze_with, Maze, MazeOptions, MazeReport};
+use crate::solver::{astar, find_tile, Cell, Route};
 use crate::player::Player;
-use crate::renderer3d::render3d;
-use crate::events::process_events;
+use crate::renderer3d::{render3d, max_view_distance, Atmosphere, Light};
+use crate::events::{process_events, apply_portals};
+use crate::portals::{place_mirrors, place_portal_pairs, Portals};
 use crate::texture::Texture;
 use crate::textures::TextureAtlas;
-use crate::sprites::{Sprite, SpriteAnim, render_sprites};
+use crate::sprites::{Sprite, SpriteAnim, SpriteAnchor, render_sprites};
+use crate::particles::ParticleSystem;
+use crate::decals::{Decal, DecalLibrary, DecalMap, DEFAULT_RECT};
+use crate::caster::{cast_ray, hit_face, RaySegment};
+use crate::level::{discover_levels, load_level, Generator, LevelDef, LevelSource, LightSpec, ProcSpec, SpriteSpec};
+use crate::records::{BestTimes, RunLog};
+use crate::endless::EndlessRun;
+use crate::daily::{daily_level, result_line, Date};
+use crate::sharecode::{level_code, level_from_code};
+use crate::export::Overlays;
+use crate::tile::{Tile, TILES};
+use crate::dungeon::{make_dungeon, Room};
+use crate::storeys::{destination, route_to_goal, stack};
+use crate::painters::{paint_walls, WallPaint};
+use crate::metrics::{analyze_storeys, level_score, report_json, report_text};
 
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use raylib::prelude::*;
+use std::collections::HashMap;
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -33,28 +72,175 @@ enum GameState {
     Title,
     Playing,
     Win,
+    RunOver, // fin de una carrera del modo sin fin
 }
 
-#[derive(Clone, Copy)]
-struct LevelConfig {
-    name: &'static str,
-    cells: (usize, usize), // (cell_w, cell_h)
+/// Obstáculo circular para colisiones: (x, y, radio).
+type Obstacle = (f32, f32, f32);
+
+/// Texturas ya cargadas, por nombre o ruta.
+type TextureCache = HashMap<String, Rc<Texture>>;
+
+/// Rebotes en espejos + saltos por portales que sigue cada rayo.
+const MAX_RAY_DEPTH: u32 = 4;
+
+/// Filas de la lista de niveles visibles a la vez en el menú (como máximo).
+const MENU_ROWS: usize = 8;
+
+/// Errores de carga de niveles que se listan en el menú (el resto, en la consola).
+const MENU_ERRORS: usize = 3;
+
+/// Lo que hay en un piso además del mapa.
+struct Storey {
+    sprites: Vec<Sprite>,
+    obstacles: Vec<Obstacle>,
+    atmo: Atmosphere, // cada piso con sus luces
+    decals: DecalMap,
+    portals: Portals,
+}
+
+/// Todo lo que se reconstruye al empezar (o reintentar) un nivel.
+struct Level {
+    mazes: Vec<Maze>, // un mapa por piso (así lo esperan `storeys::*`)
+    storeys: Vec<Storey>,
+    current: usize,   // piso donde está el jugador
+    report: MazeReport,
+    atlas: TextureAtlas,
+    seed: u64,
+    rooms: Vec<Vec<Room>>, // salas de cada piso (vacío si no es `rooms`)
+}
+
+impl Level {
+    fn maze(&self) -> &Maze {
+        &self.mazes[self.current]
+    }
+
+    fn here(&self) -> &Storey {
+        &self.storeys[self.current]
+    }
+
+    fn here_mut(&mut self) -> &mut Storey {
+        &mut self.storeys[self.current]
+    }
 }
 
-// Tres laberintos más pequeños 
-const LEVELS: [LevelConfig; 3] = [
-    LevelConfig { name: "Nivel 1 (12x9)",  cells: (12, 9)  },
-    LevelConfig { name: "Nivel 2 (14x10)", cells: (14, 10) },
-    LevelConfig { name: "Nivel 3 (16x12)", cells: (16, 12) },
-];
+/// Lado de un tile en unidades de mundo.
+const BLOCK_SIZE: usize = 64;
 
-fn draw_title_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32, sel: usize) {
+/// Duración del fundido al cambiar de piso (segundos; a la mitad se cambia).
+const STOREY_FADE: f32 = 0.6;
+
+/// Opciones de línea de comandos: `--seed N`, `--level N` (desde 1) y
+/// `--levels RUTA` (archivo `.lvl` o carpeta; se listan antes que `levels/`).
+/// Con `--seed` el nivel arranca directamente, sin pasar por el menú.
+/// `--analyze` imprime el informe de dificultad de los niveles (o solo del
+/// de `--level`) sin abrir la ventana; con `--json`, en JSON. `--endless`
+/// arranca el modo sin fin (con la semilla de `--seed` si se da).
+/// `--code CÓDIGO` juega el nivel de un código compartido (ver `sharecode`).
+/// `--bake` guarda los niveles ya generados en binario (ver `bake_levels`).
+struct CliArgs {
+    seed: Option<u64>,
+    level: Option<usize>,
+    level_paths: Vec<PathBuf>,
+    analyze: bool,
+    json: bool,
+    endless: bool,
+    code: Option<String>,
+    bake: bool,
+}
+
+/// Lee la línea de comandos; un valor que falta o no se entiende es error
+/// (mejor avisar que jugar con otra semilla u otro nivel sin decirlo).
+fn parse_args() -> Result<CliArgs, String> {
+    let mut out = CliArgs { seed: None, level: None, level_paths: Vec::new(), analyze: false, json: false, endless: false, code: None, bake: false };
+    let mut args = env::args().skip(1);
+    while let Some(a) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Falta el valor de {a}"));
+        match a.as_str() {
+            "--seed" => {
+                let v = value()?;
+                out.seed = Some(v.parse().map_err(|_| format!("--seed: '{v}' no es una semilla (número entero sin signo)"))?);
+            }
+            "--level" => {
+                let v = value()?;
+                let n = v.parse::<usize>().ok().filter(|&n| n >= 1)
+                    .ok_or_else(|| format!("--level: '{v}' no es un número de nivel (1, 2, ...)"))?;
+                out.level = Some(n - 1);
+            }
+            "--levels" => out.level_paths.push(PathBuf::from(value()?)),
+            "--analyze" => out.analyze = true,
+            "--json"    => out.json = true,
+            "--endless" => out.endless = true,
+            "--code"    => out.code = Some(value()?),
+            "--bake"    => out.bake = true,
+            _ => eprintln!("Argumento desconocido: {a}"),
+        }
+    }
+    Ok(out)
+}
+
+/// Error de uso: se informa por stderr y se sale sin abrir la ventana.
+fn usage_error(msg: &str) -> ! {
+    eprintln!("{msg}");
+    std::process::exit(2)
+}
+
+/// `--level` fuera de rango (hay `count` archivos de nivel).
+fn check_level_arg(cli: &CliArgs, count: usize) {
+    if let Some(n) = cli.level.filter(|&n| n >= count) {
+        usage_error(&format!("--level: no existe el nivel {} (hay {count})", n + 1));
+    }
+}
+
+/// Semilla con que se juega `def`: la del menú si hay, si no la del archivo
+/// (los niveles hechos a mano no usan semilla: 0); `None` = al azar.
+fn chosen_seed(def: &LevelDef, menu_seed: Option<u64>) -> Option<u64> {
+    let file_seed = match &def.source {
+        LevelSource::Procedural(p) => p.seed,
+        LevelSource::Grid(_) => Some(0),
+    };
+    menu_seed.or(file_seed)
+}
+
+/// Clave de récords: el id del nivel, más la semilla cuando no es la del
+/// archivo (un nivel procedural con otra semilla es otro mapa).
+fn record_key(def: &LevelDef, seed: u64) -> String {
+    match &def.source {
+        LevelSource::Procedural(p) if p.seed != Some(seed) => format!("{}#{seed}", def.id),
+        _ => def.id.clone(),
+    }
+}
+
+/// Semilla nueva al azar (corta, para poder dictarla).
+fn random_seed() -> u64 {
+    rand::random::<u32>() as u64
+}
+
+/// Primera fila visible de la lista para que `sel` quede a la vista.
+fn menu_scroll(sel: usize, len: usize, rows: usize) -> usize {
+    sel.saturating_sub(rows / 2).min(len.saturating_sub(rows))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_title_screen(
+    d: &mut raylib::drawing::RaylibDrawHandle,
+    w: i32,
+    h: i32,
+    levels: &[LevelDef],
+    best: &BestTimes,
+    best_run: Option<(usize, f32)>,
+    sel: usize,
+    seed_input: &str,
+    editing_seed: bool,
+    fixed_from: usize,
+    load_errors: &[String],
+) {
     use raylib::prelude::*;
     d.clear_background(Color::DARKBLUE);
 
     let title   = "3D Maze por Andres Mazariegos";
     let subtitle= "Selecciona nivel y presiona ENTER";
-    let hint2   = "F11: Fullscreen   ESC: Salir   (1/2/3 o flechas para elegir)";
+    let hint2   = "F11: Fullscreen   ESC: Salir   (flechas, RePág/AvPág para elegir)   TAB: semilla   E: sin fin   V: pegar código";
 
     let title_size = 44;
     let text_size  = 22;
@@ -65,28 +251,83 @@ fn draw_title_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32,
     let sw = d.measure_text(subtitle, text_size);
     d.draw_text(subtitle, (w - sw)/2, h/6 + 50, text_size, Color::LIGHTGRAY);
 
-    // opciones de nivel
+    // opciones de nivel (ventana que sigue a la selección)
     let base_y = h/3;
-    for (i, lv) in LEVELS.iter().enumerate() {
-        let txt = lv.name;
+    let rows  = MENU_ROWS.min(levels.len());
+    let first = menu_scroll(sel, levels.len(), rows);
+    for (row, i) in (first..first + rows).enumerate() {
+        let lv = &levels[i];
+        let mut txt = match &lv.author {
+            Some(author) => format!("{} - {} ({author})", lv.name, lv.kind()),
+            None         => format!("{} - {}", lv.name, lv.kind()),
+        };
+        let menu_seed = if i >= fixed_from { None } else { seed_input.parse().ok() };
+        if let Some(t) = chosen_seed(lv, menu_seed).and_then(|s| best.get(&record_key(lv, s))) {
+            txt.push_str(&format!("   récord {t:.2} s"));
+        }
         let col = if i == sel { Color::YELLOW } else { Color::RAYWHITE };
         let sz  = if i == sel { 26 } else { 22 };
-        let twx = d.measure_text(txt, sz);
-        d.draw_text(txt, (w - twx)/2, base_y + (i as i32)*40, sz, col);
+        let twx = d.measure_text(&txt, sz);
+        d.draw_text(&txt, (w - twx)/2, base_y + (row as i32)*40, sz, col);
+    }
+    // hay más arriba / abajo
+    let list_x = w/2 - 10;
+    if first > 0 {
+        d.draw_text("...", list_x, base_y - 24, 20, Color::LIGHTGRAY);
+    }
+    if first + rows < levels.len() {
+        d.draw_text("...", list_x, base_y + (rows as i32)*40 - 12, 20, Color::LIGHTGRAY);
+    }
+
+    // semilla (vacía = al azar)
+    let seed_txt = match (seed_input.is_empty(), editing_seed) {
+        (true,  false) => "Semilla: al azar".to_string(),
+        (_,     true)  => format!("Semilla: {seed_input}_"),
+        (false, false) => format!("Semilla: {seed_input}"),
+    };
+    let col = if editing_seed { Color::YELLOW } else { Color::LIGHTGRAY };
+    let stw = d.measure_text(&seed_txt, text_size);
+    d.draw_text(&seed_txt, (w - stw)/2, base_y + (rows as i32)*40 + 20, text_size, col);
+
+    if let Some((depth, secs)) = best_run {
+        let txt = format!("Sin fin: mejor carrera, profundidad {depth} en {secs:.1} s");
+        let bw = d.measure_text(&txt, 20);
+        d.draw_text(&txt, (w - bw)/2, base_y + (rows as i32)*40 + 50, 20, Color::SKYBLUE);
+    }
+
+    // archivos de nivel que no cargaron (los primeros; el resto en la consola)
+    let mut notes: Vec<String> = Vec::new();
+    if fixed_from == 0 && load_errors.is_empty() {
+        notes.push("No hay archivos de nivel en levels/".to_string());
+    }
+    if !load_errors.is_empty() {
+        notes.push(format!("{} archivo(s) de nivel con errores:", load_errors.len()));
+        notes.extend(load_errors.iter().take(MENU_ERRORS).cloned());
+        if load_errors.len() > MENU_ERRORS {
+            notes.push(format!("... y {} más (ver consola)", load_errors.len() - MENU_ERRORS));
+        }
+    }
+    for (k, txt) in notes.iter().rev().enumerate() {
+        let bw = d.measure_text(txt, 18);
+        d.draw_text(txt, (w - bw)/2, (h*5)/6 - 28 - 22 * k as i32, 18, Color::ORANGE);
     }
 
     let hw2 = d.measure_text(hint2, 18);
     d.draw_text(hint2, (w - hw2)/2, (h*5)/6, 18, Color::LIGHTGRAY);
 }
 
-fn draw_win_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32, seconds: f32) {
+#[allow(clippy::too_many_arguments)]
+fn draw_win_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32, seconds: f32, par: Option<f32>, best: Option<f32>, new_record: bool, seed: u64, daily: Option<&str>, code: Option<&str>) {
     use raylib::prelude::*;
     d.clear_background(Color::DARKGREEN);
 
     let title = "¡Nivel completado!";
-    let time  = format!("Tiempo: {:.2} s", seconds.max(0.0));
+    let time  = match par {
+        Some(par) => format!("Tiempo: {:.2} s   (par {par:.0} s)", seconds.max(0.0)),
+        None      => format!("Tiempo: {:.2} s", seconds.max(0.0)),
+    };
     let hint1 = "ENTER: Volver al menú";
-    let hint2 = "R: Reintentar el mismo nivel   F11: Fullscreen";
+    let hint2 = "R: Reintentar el mismo nivel   F5: Exportar plano   F11: Fullscreen";
 
     let title_size = 44;
     let text_size  = 22;
@@ -97,41 +338,368 @@ fn draw_win_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32, se
     let tw2 = d.measure_text(&time, text_size);
     d.draw_text(&time, (w - tw2)/2, h/3 + 30, text_size, Color::YELLOW);
 
+    let seed_txt = format!("Semilla: {seed}");
+    let tw3 = d.measure_text(&seed_txt, 20);
+    d.draw_text(&seed_txt, (w - tw3)/2, h/3 + 60, 20, Color::LIGHTGRAY);
+
+    let best_txt = match (new_record, best) {
+        (true, _)        => "¡Nuevo récord!".to_string(),
+        (false, Some(b)) => format!("Récord: {b:.2} s"),
+        (false, None)    => String::new(),
+    };
+    let tw4 = d.measure_text(&best_txt, 20);
+    d.draw_text(&best_txt, (w - tw4)/2, h/3 + 88, 20, if new_record { Color::GOLD } else { Color::LIGHTGRAY });
+
+    // desafío diario: resultado para compartir
+    let mut y = h/3 + 120;
+    if let Some(line) = daily {
+        let lw = d.measure_text(line, 18);
+        d.draw_text(line, (w - lw)/2, y, 18, Color::SKYBLUE);
+        let copy = "C: copiar resultado";
+        let cw = d.measure_text(copy, 18);
+        d.draw_text(copy, (w - cw)/2, y + 24, 18, Color::LIGHTGRAY);
+        y += 56;
+    }
+
+    // código del nivel (solo procedurales)
+    if let Some(code) = code {
+        let txt = format!("Código: {code}   (K: copiar)");
+        let cw = d.measure_text(&txt, 18);
+        d.draw_text(&txt, (w - cw)/2, y, 18, Color::LIGHTGRAY);
+    }
+
     let hw1 = d.measure_text(hint1, 20);
     d.draw_text(hint1, (w - hw1)/2, (h*2)/3, 20, Color::RAYWHITE);
     let hw2 = d.measure_text(hint2, 18);
     d.draw_text(hint2, (w - hw2)/2, (h*2)/3 + 32, 18, Color::LIGHTGRAY);
 }
 
-fn place_player_at_start(player: &mut Player, maze: &Maze, block_size: usize) {
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c == 'p' {
-                player.pos.x = (i * block_size + block_size / 2) as f32;
-                player.pos.y = (j * block_size + block_size / 2) as f32;
-                return;
+/// Resumen de una carrera sin fin terminada.
+fn draw_run_screen(d: &mut raylib::drawing::RaylibDrawHandle, w: i32, h: i32, run: &EndlessRun, total: f32, best: Option<(usize, f32)>, record: bool) {
+    use raylib::prelude::*;
+    d.clear_background(Color::new(20, 30, 60, 255));
+
+    let lines = [
+        ("Fin de la carrera".to_string(), 44, Color::WHITE),
+        (format!("Profundidad {}   Tiempo total {total:.2} s", run.depth), 24, Color::YELLOW),
+        (format!("Semilla de la carrera: {}", run.seed), 20, Color::LIGHTGRAY),
+        (match (record, best) {
+            (true, _)              => "¡Mejor carrera!".to_string(),
+            (false, Some((d, s)))  => format!("Mejor: profundidad {d} en {s:.1} s"),
+            (false, None)          => String::new(),
+        }, 20, if record { Color::GOLD } else { Color::LIGHTGRAY }),
+    ];
+    let mut y = h/3 - 20;
+    for (txt, size, col) in &lines {
+        let tw = d.measure_text(txt, *size);
+        d.draw_text(txt, (w - tw)/2, y, *size, *col);
+        y += size + 14;
+    }
+
+    let hint = "ENTER: Volver al menú   R: Repetir la carrera   F5: Exportar plano";
+    let hw = d.measure_text(hint, 20);
+    d.draw_text(hint, (w - hw)/2, (h*2)/3, 20, Color::RAYWHITE);
+}
+
+/// Tiles recorridos según el rastro (sin contar saltos de portal ni de piso).
+fn walked_tiles(trail: &[(usize, f32, f32)]) -> f32 {
+    trail.windows(2)
+        .filter(|w| w[0].0 == w[1].0)
+        .map(|w| (w[1].1 - w[0].1).hypot(w[1].2 - w[0].2))
+        .filter(|&step| step < 1.0)
+        .sum()
+}
+
+/// Nivel en juego: el de la carrera sin fin si hay una, si no el del menú.
+fn current_def<'a>(levels: &'a [LevelDef], selected: usize, run: Option<&'a EndlessRun>) -> &'a LevelDef {
+    run.map_or(&levels[selected], |r| &r.def)
+}
+
+/// Pone al jugador en el inicio 'p', en el piso que esté.
+fn place_player_at_start(player: &mut Player, level: &mut Level, block_size: usize) {
+    let start = level.mazes.iter().enumerate().find_map(|(s, m)| m.find(Tile::Start).map(|c| (s, c)));
+    if let Some((s, (i, j))) = start {
+        level.current = s;
+        player.pos.x = (i * block_size + block_size / 2) as f32;
+        player.pos.y = (j * block_size + block_size / 2) as f32;
+    }
+}
+
+/// Ruta de la pista dentro del piso actual (hasta la escalera, si la meta
+/// está en otro) y pasos totales hasta la meta.
+fn hint_route(level: &Level, here: Cell) -> Option<(Route, usize)> {
+    let local = find_tile(level.maze(), Tile::Goal).and_then(|goal| astar(level.maze(), here, goal));
+    if let Some(route) = local {
+        let steps = route.length;
+        return Some((route, steps));
+    }
+    let path = route_to_goal(&level.mazes, (level.current, here))?;
+    let cells = path.iter().take_while(|&&(s, _)| s == level.current).map(|&(_, c)| c).collect();
+    Some((Route::from_cells(cells), path.len() - 1))
+}
+
+fn find_start(maze: &Maze, block_size: usize) -> (f32, f32) {
+    if let Some((i, j)) = maze.find(Tile::Start) {
+        return ((i * block_size + block_size / 2) as f32,
+                (j * block_size + block_size / 2) as f32);
+    }
+    (block_size as f32 * 0.5, block_size as f32 * 0.5) // fallback
+}
+
+/// Carga (o reutiliza) una textura por ruta.
+fn cached_texture(cache: &mut TextureCache, path: &str) -> Option<Rc<Texture>> {
+    if let Some(t) = cache.get(path) {
+        return Some(t.clone());
+    }
+    match Texture::from_file(path) {
+        Ok(t) => {
+            let t = Rc::new(t);
+            cache.insert(path.to_string(), t.clone());
+            Some(t)
+        }
+        Err(e) => {
+            eprintln!("No se pudo cargar la textura '{path}': {e}");
+            None
+        }
+    }
+}
+
+/// Construye un nivel a partir de su definición. Los procedurales salen de
+/// `seed` (la misma semilla reproduce el mismo nivel; cada piso usa una
+/// derivada) y además reciben cajas (pegadas a las paredes, o en las
+/// esquinas de las salas); los hechos a mano usan solo lo que trae el archivo.
+fn build_level(
+    def: &LevelDef,
+    block_size: usize,
+    seed: u64,
+    base_atlas: &TextureAtlas,
+    textures: &mut TextureCache,
+    decal_lib: &mut DecalLibrary,
+) -> Level {
+    let bs = block_size as f32;
+    let crate_anim = SpriteAnim { frames: textures.get("crate").into_iter().cloned().collect(), fps: 1.0 };
+
+    let (mazes, report, rooms) = generate_level(def, seed);
+
+    // Texturas de muro propias del nivel
+    let mut atlas = base_atlas.clone();
+    for (tile, path) in &def.textures {
+        if let Some(tex) = cached_texture(textures, &def.resolve_path(path)) {
+            atlas.insert(*tile, tex);
+        }
+    }
+
+    // Cielo, suelo y niebla (las luces van por piso)
+    let mut base_atmo = Atmosphere::classic(max_view_distance(&mazes[0], block_size));
+    if let Some(sky) = def.sky { base_atmo.sky = sky; }
+    if let Some(floor) = def.floor { base_atmo.floor = floor; }
+    if let Some((fog, dist)) = def.fog {
+        base_atmo.fog = fog;
+        if let Some(dist) = dist { base_atmo.fog_distance = dist * bs; }
+    }
+    if let Some(ambient) = def.ambient { base_atmo.ambient = ambient; }
+
+    let mut storeys = Vec::new();
+    for (s, (maze, rooms)) in mazes.iter().zip(&rooms).enumerate() {
+        let (mut sprites, mut obstacles) = level_crates(def, maze, rooms, block_size, &crate_anim);
+
+        // Sprites del archivo
+        for spec in def.sprites.iter().filter(|sp| sp.storey == s) {
+            let anim = if spec.texture == "crate" {
+                crate_anim.clone()
+            } else {
+                let Some(tex) = cached_texture(textures, &def.resolve_path(&spec.texture)) else { continue };
+                SpriteAnim { frames: vec![tex], fps: 1.0 }
+            };
+            let (x, y) = (spec.x * bs, spec.y * bs);
+            if spec.radius > 0.0 {
+                obstacles.push((x, y, spec.radius * bs));
+            }
+            sprites.push(Sprite {
+                x,
+                y,
+                z: spec.z * bs,
+                anchor: spec.anchor,
+                size: spec.size * bs,
+                anim,
+                wobble_amp: 0.0,
+                wobble_freq: 0.0,
+                phase: 0.0,
+            });
+        }
+
+        // Marcador flotando sobre la meta (sin colisión)
+        for ((i, j), _) in maze.cells().filter(|&(_, t)| t == Tile::Goal) {
+            sprites.push(Sprite {
+                x: (i as f32 + 0.5) * bs,
+                y: (j as f32 + 0.5) * bs,
+                z: bs * 0.55,
+                anchor: SpriteAnchor::Center,
+                size: bs * 0.25,
+                anim: crate_anim.clone(),
+                wobble_amp: 3.0,
+                wobble_freq: 0.8,
+                phase: 0.0,
+            });
+        }
+
+        // Luces del archivo y de las salas
+        let mut atmo = base_atmo.clone();
+        atmo.lights = def.lights.iter()
+            .filter(|l| l.storey == s)
+            .map(|l| Light { x: l.x * bs, y: l.y * bs, radius: l.radius * bs, color: l.color })
+            .collect();
+        if let Some((radius, color)) = def.room_light {
+            for r in rooms {
+                let (i, j) = r.center();
+                atmo.lights.push(Light { x: (i as f32 + 0.5) * bs, y: (j as f32 + 0.5) * bs, radius: radius * bs, color });
+            }
+        }
+
+        // Calcomanías del archivo
+        let mut decals = DecalMap::new();
+        for spec in def.decals.iter().filter(|d| d.storey == s) {
+            match decal_lib.resolve(&def.resolve_path(&spec.name)) {
+                Some(tex) => decals.add(spec.i, spec.j, spec.face, Decal { tex, rect: spec.rect, flip_u: false, chalk: false }),
+                None => eprintln!("No se encontró la calcomanía '{}'", spec.name),
             }
         }
+
+        let portals = Portals::from_maze(maze, MAX_RAY_DEPTH);
+        storeys.push(Storey { sprites, obstacles, atmo, decals, portals });
     }
+
+    Level { mazes, storeys, current: 0, report, atlas, seed, rooms }
 }
 
-fn find_start(maze: &Maze, block_size: usize) -> (f32, f32) {
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c == 'p' {
-                return ((i * block_size + block_size / 2) as f32,
-                        (j * block_size + block_size / 2) as f32);
+/// Mapas del nivel (uno por piso), lo que hizo la generación y las salas de
+/// cada piso. No carga nada, así que sirve también para `--analyze`.
+fn generate_level(def: &LevelDef, seed: u64) -> (Vec<Maze>, MazeReport, Vec<Vec<Room>>) {
+    match &def.source {
+        LevelSource::Procedural(p) => {
+            let (mut mazes, report, rooms) = match p.difficulty {
+                Some(target) => fit_difficulty(p, seed, target),
+                None => generate_storeys(p, p.cells, seed),
+            };
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(1));
+            for maze in &mut mazes {
+                place_mirrors(maze, p.mirrors, &mut rng);
+                place_portal_pairs(maze, p.portal_pairs);
             }
+            (mazes, report, rooms)
+        }
+        LevelSource::Grid(mazes) => {
+            let path_len = storeys::solve(mazes).map_or(0, |r| r.len() - 1);
+            (mazes.clone(), MazeReport { path_len, ..Default::default() }, vec![Vec::new(); mazes.len()])
         }
     }
-    (block_size as f32 * 0.5, block_size as f32 * 0.5) // fallback
 }
 
-/// Construye un nivel: maze + posiciones de sprites (crates pegadas a pared) + obstáculos
-fn build_level(cfg: LevelConfig, block_size: usize) -> (Maze, Vec<Sprite>, Vec<(f32,f32,f32)>) {
-    let maze = make_maze(cfg.cells.0, cfg.cells.1);
+/// Los pisos de un nivel procedural con `cells` celdas, apilados si son varios.
+fn generate_storeys(p: &ProcSpec, cells: (usize, usize), seed: u64) -> (Vec<Maze>, MazeReport, Vec<Vec<Room>>) {
+    let mut mazes = Vec::new();
+    let mut rooms: Vec<Vec<Room>> = Vec::new();
+    let mut report = MazeReport::default();
+    let storey_seed = |s: usize| seed ^ (s as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15); // piso 0 = `seed`
+    for s in 0..p.storeys {
+        let seed = storey_seed(s);
+        let (maze, r, rs) = match p.generator {
+            Generator::Maze(algo) => {
+                let opts = MazeOptions { braid: p.braid, placement: p.placement };
+                let (maze, r) = make_maze_with(algo.generator().as_ref(), cells.0, cells.1, seed, opts);
+                (maze, r, Vec::new())
+            }
+            Generator::Rooms(opts) => {
+                let d = make_dungeon(cells.0, cells.1, seed, opts);
+                (d.maze, d.report, d.rooms)
+            }
+        };
+        report.loops_added += r.loops_added;
+        report.path_len = r.path_len;
+        mazes.push(maze);
+        rooms.push(rs);
+    }
+    if mazes.len() > 1 {
+        match stack(&mut mazes, p.link) {
+            Some(steps) => report.path_len = steps as usize / 2,
+            // Algún piso sin lugar para la meta (mapas diminutos): mejor un
+            // solo piso que pisar el inicio o una escalera
+            None => return generate_storeys(&ProcSpec { storeys: 1, ..p.clone() }, cells, seed),
+        }
+    }
+    // Materiales de muro (después de apilar: `depth` sigue las escaleras)
+    if p.walls != WallPaint::default() {
+        let n = mazes.len();
+        for (s, (maze, rooms)) in mazes.iter_mut().zip(&rooms).enumerate() {
+            paint_walls(maze, &p.walls, storey_seed(s).wrapping_add(3), rooms, (s, n));
+        }
+    }
+    (mazes, report, rooms)
+}
+
+/// Prueba tamaños de hasta `p.cells` (con sus proporciones) y se queda con
+/// el de puntaje de dificultad más cercano a `target`.
+fn fit_difficulty(p: &ProcSpec, seed: u64, target: f32) -> (Vec<Maze>, MazeReport, Vec<Vec<Room>>) {
+    let longest = p.cells.0.max(p.cells.1).max(2);
+    let scaled = |n: usize, side: usize| (side * n).div_ceil(longest).max(2);
+    let mut sizes: Vec<(usize, usize)> = (2..=longest).map(|n| (scaled(n, p.cells.0), scaled(n, p.cells.1))).collect();
+    sizes.dedup();
+    sizes.into_iter()
+        .map(|cells| {
+            let level = generate_storeys(p, cells, seed);
+            let metrics = analyze_storeys(&level.0);
+            ((level_score(&metrics) - target).abs(), level)
+        })
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, level)| level)
+        .expect("al menos un tamaño")
+}
+
+/// Cajas de un piso: pegadas a las paredes, o en las esquinas de las salas;
+/// los niveles hechos a mano no reciben ninguna.
+fn level_crates(def: &LevelDef, maze: &Maze, rooms: &[Room], block_size: usize, anim: &SpriteAnim) -> (Vec<Sprite>, Vec<Obstacle>) {
+    match &def.source {
+        LevelSource::Procedural(_) if rooms.is_empty() => wall_crates(maze, block_size, anim),
+        LevelSource::Procedural(_) => room_crates(maze, rooms, block_size, anim),
+        LevelSource::Grid(_) => (Vec::new(), Vec::new()),
+    }
+}
+
+/// Una caja por sala, en una de sus esquinas (rota según la sala), salvo en
+/// la sala de inicio.
+fn room_crates(maze: &Maze, rooms: &[Room], block_size: usize, anim: &SpriteAnim) -> (Vec<Sprite>, Vec<Obstacle>) {
+    let bs = block_size as f32;
+    let start = maze.find(Tile::Start);
+    let mut sprites = Vec::new();
+    let mut obstacles = Vec::new();
+    for (k, room) in rooms.iter().enumerate() {
+        if start.is_some_and(|s| room.contains(s)) || room.w < 2 || room.h < 2 { continue; }
+        let (i, j) = room.corners()[k % 4];
+        if maze[(i, j)] != Tile::Floor { continue; }
+        // hacia la esquina de verdad: -1 en el borde izquierdo/superior
+        let sx = if i == room.x { -1.0 } else { 1.0 };
+        let sy = if j == room.y { -1.0 } else { 1.0 };
+        let cx = (i as f32 + 0.5 + sx * 0.2) * bs;
+        let cy = (j as f32 + 0.5 + sy * 0.2) * bs;
+        obstacles.push((cx, cy, bs * 0.10));
+        sprites.push(Sprite {
+            x: cx,
+            y: cy,
+            z: 0.0,
+            anchor: SpriteAnchor::Floor,
+            size: bs * 0.40,
+            anim: anim.clone(),
+            wobble_amp: 1.5,
+            wobble_freq: 2.2,
+            phase: k as f32 * 0.9,
+        });
+    }
+    (sprites, obstacles)
+}
 
-    let (px, py) = find_start(&maze, block_size);
+/// Cajas pegadas a las paredes (una de cada pocas celdas), lejos del inicio.
+fn wall_crates(maze: &Maze, block_size: usize, anim: &SpriteAnim) -> (Vec<Sprite>, Vec<Obstacle>) {
+    let (px, py) = find_start(maze, block_size);
 
     let mut sprites_world: Vec<Sprite> = Vec::new();
     let mut obstacles: Vec<(f32,f32,f32)> = Vec::new();
@@ -139,56 +707,243 @@ fn build_level(cfg: LevelConfig, block_size: usize) -> (Maze, Vec<Sprite>, Vec<(
     let crate_radius = block_size as f32 * 0.10;
     let side_offset  = block_size as f32 * 0.30;
     let center_nudge = block_size as f32 * 0.05;
-    let is_wall = |ch: char| ch == '#' || ch == 'A';
 
     #[derive(Clone, Copy)]
     struct Pos { cx: f32, cy: f32, phase: f32 }
     let mut poslist: Vec<Pos> = Vec::new();
 
-    for (j, row) in maze.iter().enumerate() {
-        for (i, &c) in row.iter().enumerate() {
-            if c != ' ' { continue; }
-            let right_wall = i + 1 < row.len()  && is_wall(maze[j][i + 1]);
-            let left_wall  = i >= 1             && is_wall(maze[j][i - 1]);
-            let down_wall  = j + 1 < maze.len() && is_wall(maze[j + 1][i]);
-            let up_wall    = j >= 1             && is_wall(maze[j - 1][i]);
-            if !(right_wall || left_wall || up_wall || down_wall) { continue; }
-            if ((i + j) % 10) != 0 { continue; }
+    for ((i, j), t) in maze.cells() {
+        if t != Tile::Floor { continue; }
+        let is_wall = |di: isize, dj: isize| maze.get_signed(i as isize + di, j as isize + dj).is_some_and(Tile::is_solid);
+        let right_wall = is_wall(1, 0);
+        let left_wall  = is_wall(-1, 0);
+        let down_wall  = is_wall(0, 1);
+        let up_wall    = is_wall(0, -1);
+        if !(right_wall || left_wall || up_wall || down_wall) { continue; }
+        if ((i + j) % 10) != 0 { continue; }
 
-            let mut cx = (i as f32 + 0.5) * block_size as f32;
-            let mut cy = (j as f32 + 0.5) * block_size as f32;
+        let mut cx = (i as f32 + 0.5) * block_size as f32;
+        let mut cy = (j as f32 + 0.5) * block_size as f32;
 
-            if      right_wall { cx += side_offset; cx -= center_nudge; }
-            else if left_wall  { cx -= side_offset; cx += center_nudge; }
-            else if down_wall  { cy += side_offset; cy -= center_nudge; }
-            else if up_wall    { cy -= side_offset; cy += center_nudge; }
+        if      right_wall { cx += side_offset; cx -= center_nudge; }
+        else if left_wall  { cx -= side_offset; cx += center_nudge; }
+        else if down_wall  { cy += side_offset; cy -= center_nudge; }
+        else if up_wall    { cy -= side_offset; cy += center_nudge; }
 
-            let dx = cx - px;
-            let dy = cy - py;
-            if (dx*dx + dy*dy).sqrt() < (block_size as f32 * 2.0) { continue; }
+        let dx = cx - px;
+        let dy = cy - py;
+        if (dx*dx + dy*dy).sqrt() < (block_size as f32 * 2.0) { continue; }
 
-            let phase = ((i as f32) * 0.37 + (j as f32) * 0.61) % (2.0 * std::f32::consts::PI);
-            poslist.push(Pos { cx, cy, phase });
-            obstacles.push((cx, cy, crate_radius));
-        }
+        let phase = ((i as f32) * 0.37 + (j as f32) * 0.61) % (2.0 * std::f32::consts::PI);
+        poslist.push(Pos { cx, cy, phase });
+        obstacles.push((cx, cy, crate_radius));
     }
 
     for p in poslist {
         sprites_world.push(Sprite {
             x: p.cx,
             y: p.cy,
+            z: 0.0,
+            anchor: SpriteAnchor::Floor,
             size: block_size as f32 * 0.40,
-            anim: SpriteAnim { frames: Vec::new(), fps: 1.0 }, // se rellena en main
+            anim: anim.clone(),
             wobble_amp: 1.5,
             wobble_freq: 2.2,
             phase: p.phase,
         });
     }
 
-    (maze, sprites_world, obstacles)
+    (sprites_world, obstacles)
+}
+
+/// Ruta base de lo que se exporta de un nivel (`exports/<id>-<semilla>`);
+/// crea la carpeta si falta.
+fn export_base(def: &LevelDef, seed: u64) -> Result<String, String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("exports");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let id = def.id.as_str();
+    let stem = id.rsplit_once('.').map_or(id, |(s, _)| s);
+    Ok(dir.join(format!("{stem}-{seed}")).to_string_lossy().into_owned())
+}
+
+/// El nivel tal como se juega, para guardarlo con `binlevel` junto a sus
+/// mapas: las cajas de un procedural y las luces de sus salas pasan a ser
+/// sprites y luces del archivo (las cajas, sin el balanceo), y las rutas
+/// quedan resueltas porque el archivo va a otra carpeta.
+fn bake_level(def: &LevelDef, mazes: &[Maze], rooms: &[Vec<Room>]) -> LevelDef {
+    let bs = BLOCK_SIZE as f32;
+    let mut baked = def.clone();
+    let no_anim = SpriteAnim { frames: Vec::new(), fps: 1.0 };
+    for (s, (maze, rooms)) in mazes.iter().zip(rooms).enumerate() {
+        let (sprites, obstacles) = level_crates(def, maze, rooms, BLOCK_SIZE, &no_anim);
+        for (sp, &(_, _, radius)) in sprites.iter().zip(&obstacles) {
+            baked.sprites.push(SpriteSpec {
+                x: sp.x / bs,
+                y: sp.y / bs,
+                texture: "crate".to_string(),
+                radius: radius / bs,
+                size: sp.size / bs,
+                anchor: sp.anchor,
+                z: sp.z / bs,
+                storey: s,
+            });
+        }
+        if let Some((radius, color)) = def.room_light {
+            for r in rooms {
+                let (i, j) = r.center();
+                baked.lights.push(LightSpec { x: i as f32 + 0.5, y: j as f32 + 0.5, radius, color, storey: s });
+            }
+        }
+    }
+    baked.room_light = None;
+    for (_, path) in &mut baked.textures { *path = def.resolve_path(path); }
+    for sp in &mut baked.sprites { sp.texture = def.resolve_path(&sp.texture); }
+    for d in &mut baked.decals { d.name = def.resolve_path(&d.name); }
+    baked
+}
+
+/// Escribe el plano del nivel (txt, png y svg) en `exports/` con la solución,
+/// el recorrido del jugador (`trail`: piso y posición en tiles) y los sprites,
+/// y otro limpio sin nada encima (`-limpio`, para imprimir y jugar en papel);
+/// un juego de archivos por piso si hay varios. Al lado van el informe de
+/// dificultad (`-metricas.txt` y `.json`) y el nivel entero en binario
+/// (`.lvlb`, se puede volver a cargar). Devuelve la ruta base.
+fn export_level(level: &Level, def: &LevelDef, trail: &[(usize, f32, f32)], block_size: usize) -> Result<String, String> {
+    let base = export_base(def, level.seed)?;
+
+    let bs = block_size as f32;
+    let solution = storeys::solve(&level.mazes).unwrap_or_default();
+    for (s, (maze, storey)) in level.mazes.iter().zip(&level.storeys).enumerate() {
+        let file = if level.mazes.len() > 1 { format!("{base}-piso{}", s + 1) } else { base.clone() };
+        let route = Route::from_cells(solution.iter().filter(|&&(k, _)| k == s).map(|&(_, c)| c).collect());
+        let trail: Vec<(f32, f32)> = trail.iter().filter(|&&(k, ..)| k == s).map(|&(_, x, y)| (x, y)).collect();
+        let sprites: Vec<(f32, f32)> = storey.sprites.iter().map(|sp| (sp.x / bs, sp.y / bs)).collect();
+        let obstacles: Vec<Obstacle> = storey.obstacles.iter().map(|&(x, y, r)| (x / bs, y / bs, r / bs)).collect();
+        let ov = Overlays { solution: Some(&route), trail: &trail, sprites: &sprites, obstacles: &obstacles };
+
+        for (file, ov) in [(file.clone(), ov), (format!("{file}-limpio"), Overlays::default())] {
+            std::fs::write(format!("{file}.txt"), export::to_text(maze, &ov)).map_err(|e| format!("{file}.txt: {e}"))?;
+            std::fs::write(format!("{file}.svg"), export::to_svg(maze, &ov, 16.0)).map_err(|e| format!("{file}.svg: {e}"))?;
+            export::save_png(maze, &ov, 16, &format!("{file}.png")).map_err(|e| format!("{file}.png: {e}"))?;
+        }
+    }
+
+    let metrics = analyze_storeys(&level.mazes);
+    let file = format!("{base}-metricas");
+    std::fs::write(format!("{file}.txt"), report_text(&def.name, level.seed, &metrics)).map_err(|e| format!("{file}.txt: {e}"))?;
+    std::fs::write(format!("{file}.json"), report_json(&def.name, level.seed, &metrics)).map_err(|e| format!("{file}.json: {e}"))?;
+
+    let bin = binlevel::to_bytes(&bake_level(def, &level.mazes, &level.rooms), &level.mazes);
+    std::fs::write(format!("{base}.lvlb"), bin).map_err(|e| format!("{base}.lvlb: {e}"))?;
+    Ok(base)
+}
+
+/// Reinicia las partículas para el piso actual (al empezar un nivel o cambiar
+/// de piso): destellos en cada meta, los emisores del archivo y uno de polvo
+/// que sigue al jugador. Devuelve el índice del emisor de polvo.
+fn spawn_level_emitters(ps: &mut ParticleSystem, level: &Level, def: &LevelDef, block_size: usize) -> usize {
+    ps.clear();
+    let bs = block_size as f32;
+    let maze = level.maze();
+    for e in def.emitters.iter().filter(|e| e.storey == level.current) {
+        ps.add_emitter(e.x * bs, e.y * bs, e.z * bs, e.params);
+    }
+    for ((i, j), _) in maze.cells().filter(|&(_, t)| t == Tile::Goal) {
+        let x = (i as f32 + 0.5) * block_size as f32;
+        let y = (j as f32 + 0.5) * block_size as f32;
+        ps.add_emitter(x, y, block_size as f32 * 0.3, particles::sparkles());
+    }
+    ps.add_emitter(0.0, 0.0, 0.0, particles::dust())
+}
+
+/// Los archivos de nivel: los de `--levels` primero, luego `levels/`.
+fn level_files(cli: &CliArgs) -> Vec<PathBuf> {
+    let levels_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("levels");
+    let mut files: Vec<PathBuf> = cli.level_paths.iter().flat_map(|p| discover_levels(p)).collect();
+    files.extend(discover_levels(&levels_dir));
+    files
+}
+
+/// Carga el archivo número `k` (desde 0) de `level_files`. Si no carga se
+/// informa; si además es el de `--level`, es error de uso (no se juega otro
+/// en su lugar).
+fn load_level_file(cli: &CliArgs, k: usize, f: &Path) -> Result<LevelDef, String> {
+    load_level(f).map_err(|e| {
+        if cli.level == Some(k) {
+            usage_error(&format!("--level {}: {}: {e}", k + 1, f.display()));
+        }
+        eprintln!("{}: {e}", f.display());
+        e.to_string()
+    })
+}
+
+/// Los niveles elegidos por línea de comandos (todos, o el de `--level`) con
+/// su semilla: la de `--seed`, la del archivo o 0. Los que no cargan se
+/// informan y se saltan.
+fn cli_levels(cli: &CliArgs) -> Vec<(LevelDef, u64)> {
+    let files = level_files(cli);
+    check_level_arg(cli, files.len());
+    files.iter().enumerate().filter(|&(k, _)| cli.level.is_none_or(|n| n == k)).filter_map(|(k, f)| {
+        let def = load_level_file(cli, k, f).ok()?;
+        let file_seed = match &def.source {
+            LevelSource::Procedural(p) => p.seed,
+            LevelSource::Grid(_) => None,
+        };
+        let seed = cli.seed.or(file_seed).unwrap_or(0);
+        Some((def, seed))
+    }).collect()
+}
+
+/// `--bake`: genera los niveles y los guarda ya hechos en `exports/` en
+/// binario (`.lvlb`, ver `binlevel`), sin abrir la ventana.
+fn bake_levels(cli: &CliArgs) {
+    for (def, seed) in cli_levels(cli) {
+        let (mazes, _, rooms) = generate_level(&def, seed);
+        let bin = binlevel::to_bytes(&bake_level(&def, &mazes, &rooms), &mazes);
+        let written = export_base(&def, seed).and_then(|base| {
+            let path = format!("{base}.lvlb");
+            std::fs::write(&path, &bin).map_err(|e| format!("{path}: {e}"))?;
+            Ok(path)
+        });
+        match written {
+            Ok(path) => println!("{path} ({} bytes)", bin.len()),
+            Err(e) => eprintln!("{e}"),
+        }
+    }
+}
+
+/// `--analyze`: informe de dificultad de cada nivel (semilla de `--seed`, la
+/// del archivo o 0), por la salida estándar.
+fn print_analysis(cli: &CliArgs) {
+    for (def, seed) in cli_levels(cli) {
+        let metrics = analyze_storeys(&generate_level(&def, seed).0);
+        if cli.json {
+            print!("{}", report_json(&def.name, seed, &metrics));
+        } else {
+            println!("{}", report_text(&def.name, seed, &metrics));
+        }
+    }
 }
 
 fn main() {
+    let cli = parse_args().unwrap_or_else(|e| usage_error(&e));
+    if cli.analyze {
+        print_analysis(&cli);
+        return;
+    }
+    if cli.bake {
+        bake_levels(&cli);
+        return;
+    }
+    let levels_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("levels");
+    let shared = match cli.code.as_deref().map(|c| level_from_code(c, levels_dir.clone())).transpose() {
+        Ok(def) => def,
+        Err(e) => {
+            eprintln!("Código inválido: {e}");
+            return;
+        }
+    };
+
     // 1) Ventana y framebuffer
     let mut screen_w = 800;
     let mut screen_h = 600;
@@ -202,6 +957,8 @@ fn main() {
 
     let mut framebuffer = Framebuffer::new(screen_w as u32, screen_h as u32);
     let mut zbuffer: Vec<f32> = vec![f32::INFINITY; framebuffer.width as usize];
+    let mut seen_rays: Vec<Vec<RaySegment>> = vec![Vec::new(); framebuffer.width as usize];
+    let mut sprite_depth: Vec<f32> = vec![f32::INFINITY; (framebuffer.width * framebuffer.height) as usize];
 
     // 2) Texturas y atlas de paredes (una vez)
     let brick_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join("bricks.jpg");
@@ -209,37 +966,107 @@ fn main() {
         Texture::from_file(brick_path.to_str().unwrap())
             .expect("No se pudo cargar textura ladrillo"),
     );
-    let mut atlas = TextureAtlas::new(brick.clone());
-    atlas.insert('#', brick.clone());
-
-    let stone = Rc::new(Texture::from_file(
-        Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join("stone.jpg").to_str().unwrap()
-    ).expect("No se pudo cargar textura piedra"));
-    atlas.insert('A', stone);
+    let mut atlas = TextureAtlas::new(brick);
+    let mut wall_files: HashMap<&str, Rc<Texture>> = HashMap::new(); // varios materiales comparten archivo
+    for info in TILES.iter().filter(|t| t.opaque) {
+        let Some(file) = info.texture else { continue };
+        let tex = wall_files.entry(file).or_insert_with(|| {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join(file);
+            Rc::new(Texture::from_file(path.to_str().unwrap())
+                .unwrap_or_else(|e| panic!("No se pudo cargar assets/{file}: {e}")))
+        });
+        let tex = if info.tint == [255; 3] { tex.clone() } else { Rc::new(tex.tinted(info.tint)) };
+        atlas.insert(info.tile, tex);
+    }
 
-    // Sprite crate (anim 1 frame, se comparte entre niveles)
+    // Sprite crate (se comparte entre niveles; los archivos lo nombran "crate")
     let crate_tex = Rc::new(Texture::from_file(
         Path::new(env!("CARGO_MANIFEST_DIR")).join("assets").join("crate.png").to_str().unwrap()
     ).expect("No se pudo cargar assets/crate.png"));
-    let crate_anim = SpriteAnim { frames: vec![crate_tex.clone()], fps: 1.0 };
+    let mut textures = TextureCache::new();
+    textures.insert("crate".to_string(), crate_tex);
+
+    // 3) Niveles desde disco: los de `--levels` primero, luego `levels/`
+    // (puede no haber ninguno: quedan el desafío del día, el modo sin fin y los códigos)
+    // (`--level N` es el archivo N aunque fallen otros antes: `cli_level` es
+    // su lugar en `levels`)
+    let files = level_files(&cli);
+    check_level_arg(&cli, files.len());
+    let mut load_errors: Vec<String> = Vec::new();
+    let mut levels: Vec<LevelDef> = Vec::new();
+    let mut cli_level = None;
+    for (k, f) in files.iter().enumerate() {
+        match load_level_file(&cli, k, f) {
+            Ok(def) => {
+                if cli.level == Some(k) { cli_level = Some(levels.len()); }
+                levels.push(def);
+            }
+            Err(e) => {
+                let name = f.file_name().map_or(f.display().to_string(), |n| n.to_string_lossy().into_owned());
+                load_errors.push(format!("{name}: {e}"));
+            }
+        }
+    }
+
+    // Desafío del día: al final de la lista, para no mover los números de `--level`
+    let today = Date::today();
+    let daily_index = levels.len();
+    levels.push(daily_level(today));
+    let mut daily_result: Option<String> = None;
+
+    // Nivel de un código compartido (`--code` o V en el menú), después del diario
+    let mut code_index: Option<usize> = None;
+    if let Some(def) = shared {
+        code_index = Some(levels.len());
+        levels.push(def);
+    }
+    let mut share_code: Option<String> = None; // código del nivel en juego
+    let mut best_times = BestTimes::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("records.txt"));
+    let mut run_log = RunLog::load(Path::new(env!("CARGO_MANIFEST_DIR")).join("runs.txt"));
+    let mut new_record = false;
 
     // Parámetros de mundo
-    let block_size: usize = 64;
+    let block_size: usize = BLOCK_SIZE;
+
+    // Calcomanías de pared (tiza del jugador + las del nivel)
+    let mut decal_lib = DecalLibrary::builtin();
+    let chalk_arrow = decal_lib.resolve("arrow").expect("calcomanía integrada");
 
     // === Inicialización por defecto para evitar E0381 ===
-    let default_cfg = LEVELS[0];
-    let mut maze = make_maze(default_cfg.cells.0, default_cfg.cells.1);
-    let mut sprites_world: Vec<Sprite> = Vec::new();
-    let mut obstacles: Vec<(f32,f32,f32)> = Vec::new();
+    let mut level = build_level(&levels[0], block_size, 0, &atlas, &mut textures, &mut decal_lib);
 
     // Player y tiempos
     let mut player = Player::new(0.0, 0.0, std::f32::consts::PI / 4.0, std::f32::consts::PI / 3.0);
-    place_player_at_start(&mut player, &maze, block_size);
+    place_player_at_start(&mut player, &mut level, block_size);
     let mut level_time: f32 = 0.0;
     let mut win_time: Option<f32> = None;
 
-    // Selección de nivel en el menú
-    let mut selected_level: usize = 0;
+    // Selección de nivel y semilla en el menú (o por línea de comandos)
+    let mut selected_level: usize = code_index.or(cli_level).unwrap_or(0);
+    let mut seed_input = cli.seed.map(|s| s.to_string()).unwrap_or_default();
+    let mut editing_seed = false;
+    let mut autostart = (cli.seed.is_some() || code_index.is_some()) && !cli.endless;
+    let mut autostart_endless = cli.endless;
+
+    // Carrera del modo sin fin en curso (None = niveles del menú)
+    let mut run: Option<EndlessRun> = None;
+    let mut run_record = false;
+
+    // Partículas (polvo, destellos, choques)
+    let mut particles = ParticleSystem::new(800);
+    let mut dust_emitter = spawn_level_emitters(&mut particles, &level, &levels[0], block_size);
+    let mut was_bumping = false;
+
+    // Pista (H): ruta a la meta en el minimapa
+    let mut show_hint = false;
+    let mut used_hint = false; // para el resultado del desafío diario
+
+    // Cambio de piso en curso: (segundos desde que empezó, piso destino)
+    let mut transition: Option<(f32, usize)> = None;
+
+    // Recorrido del jugador (piso y posición en tiles) y aviso de exportación (F5)
+    let mut trail: Vec<(usize, f32, f32)> = Vec::new();
+    let mut notice: Option<(String, f32)> = None; // (texto, segundos restantes)
 
     // Estado inicial
     let mut state = GameState::Title;
@@ -259,49 +1086,171 @@ fn main() {
             screen_h = cur_h;
             framebuffer.resize(screen_w as u32, screen_h as u32);
             zbuffer.resize(framebuffer.width as usize, f32::INFINITY);
+            seen_rays.resize(framebuffer.width as usize, Vec::new());
+            sprite_depth.resize((framebuffer.width * framebuffer.height) as usize, f32::INFINITY);
         }
 
+        // Nivel a (re)iniciar este frame, con su semilla
+        let mut start: Option<u64> = None;
+
         // --- Lógica por estado (sin dibujar aún) ---
         match state {
+            GameState::Title if editing_seed => {
+                // escribir semilla: dígitos, BACKSPACE borra, TAB/ENTER termina
+                while let Some(ch) = rl.get_char_pressed() {
+                    if ch.is_ascii_digit() && seed_input.len() < 19 { seed_input.push(ch); }
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) { seed_input.pop(); }
+                if rl.is_key_pressed(KeyboardKey::KEY_TAB) || rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    editing_seed = false;
+                }
+            }
             GameState::Title => {
-                // cambiar selección (flechas o 1/2/3)
+                if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+                    while rl.get_char_pressed().is_some() {} // descartar teclas previas
+                    editing_seed = true;
+                }
+
+                // cambiar selección (flechas dan la vuelta; RePág/AvPág/Inicio/Fin saltan)
+                let last = levels.len() - 1;
                 if rl.is_key_pressed(KeyboardKey::KEY_UP) || rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
-                    if selected_level == 0 { selected_level = LEVELS.len() - 1; }
+                    if selected_level == 0 { selected_level = last; }
                     else { selected_level -= 1; }
                 }
                 if rl.is_key_pressed(KeyboardKey::KEY_DOWN) || rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
-                    selected_level = (selected_level + 1) % LEVELS.len();
+                    selected_level = (selected_level + 1) % levels.len();
                 }
-                if rl.is_key_pressed(KeyboardKey::KEY_ONE)   { selected_level = 0; }
-                if rl.is_key_pressed(KeyboardKey::KEY_TWO)   { selected_level = 1.min(LEVELS.len()-1); }
-                if rl.is_key_pressed(KeyboardKey::KEY_THREE) { selected_level = 2.min(LEVELS.len()-1); }
-
-                // Iniciar nivel seleccionado
-                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
-                    let (mz, mut spr, obs) = build_level(LEVELS[selected_level], block_size);
-                    for s in spr.iter_mut() { s.anim = crate_anim.clone(); }
-                    maze = mz;
-                    sprites_world = spr;
-                    obstacles = obs;
-
-                    place_player_at_start(&mut player, &maze, block_size);
-                    level_time = 0.0;
-                    win_time = None;
-                    state = GameState::Playing;
+                if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP)   { selected_level = selected_level.saturating_sub(MENU_ROWS); }
+                if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) { selected_level = (selected_level + MENU_ROWS).min(last); }
+                if rl.is_key_pressed(KeyboardKey::KEY_HOME)      { selected_level = 0; }
+                if rl.is_key_pressed(KeyboardKey::KEY_END)       { selected_level = last; }
+
+                // V: pegar un código compartido (reemplaza al anterior) y jugarlo
+                if rl.is_key_pressed(KeyboardKey::KEY_V) {
+                    let text = rl.get_clipboard_text().unwrap_or_default();
+                    match level_from_code(&text, levels_dir.clone()) {
+                        Ok(def) => {
+                            let i = *code_index.get_or_insert(levels.len());
+                            if i == levels.len() { levels.push(def); } else { levels[i] = def; }
+                            selected_level = i;
+                            autostart = true;
+                        }
+                        Err(e) => notice = Some((format!("Código: {e}"), 4.0)),
+                    }
+                }
+
+                // Iniciar nivel seleccionado (semilla del menú > la del archivo > al azar;
+                // el desafío diario y los códigos siempre usan la suya)
+                if autostart || rl.is_key_pressed(KeyboardKey::KEY_ENTER) || rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+                    autostart = false;
+                    let fixed = selected_level == daily_index || Some(selected_level) == code_index;
+                    let menu_seed = if fixed { None } else { seed_input.parse().ok() };
+                    run = None;
+                    start = Some(chosen_seed(&levels[selected_level], menu_seed).unwrap_or_else(random_seed));
+                }
+
+                // E: modo sin fin (la semilla del menú es la de toda la carrera)
+                if autostart_endless || rl.is_key_pressed(KeyboardKey::KEY_E) {
+                    autostart_endless = false;
+                    let r = EndlessRun::new(seed_input.parse().ok().unwrap_or_else(random_seed));
+                    start = Some(r.level_seed());
+                    run = Some(r);
                 }
             }
             GameState::Playing => {
                 let dt = rl.get_frame_time();
                 level_time += dt;
                 // eventos + colisiones (mapa + obstáculos)
-                process_events(&rl, &mut player, &maze, block_size, dt, &obstacles);
+                let prev = player.pos;
+                let bumped = transition.is_none()
+                    && process_events(&rl, &mut player, level.maze(), block_size, dt, &level.here().obstacles);
+                apply_portals(&mut player, prev, level.maze(), &level.here().portals, block_size);
+
+                // escaleras y ascensores: al entrar en la casilla, fundido y cambio de piso
+                let cell = |p: Vector2| (p.x as usize / block_size, p.y as usize / block_size);
+                if transition.is_none()
+                    && cell(prev) != cell(player.pos)
+                    && let Some(to) = destination(&level.mazes, level.current, cell(player.pos))
+                {
+                    transition = Some((0.0, to));
+                }
+                if let Some((t, to)) = &mut transition {
+                    let half = STOREY_FADE / 2.0;
+                    let before = *t;
+                    *t += dt;
+                    if before < half && *t >= half {
+                        level.current = *to;
+                        dust_emitter = spawn_level_emitters(&mut particles, &level, current_def(&levels, selected_level, run.as_ref()), block_size);
+                    }
+                    if *t >= STOREY_FADE { transition = None; }
+                }
+
+                // partículas: polvo al caminar, nube al chocar
+                let dust = &mut particles.emitters[dust_emitter];
+                dust.x = player.pos.x;
+                dust.y = player.pos.y;
+                dust.active = prev.x != player.pos.x || prev.y != player.pos.y;
+                if bumped && !was_bumping {
+                    let reach = block_size as f32 * 0.25;
+                    particles.burst(
+                        player.pos.x + player.a.cos() * reach,
+                        player.pos.y + player.a.sin() * reach,
+                        block_size as f32 * 0.35,
+                        10,
+                        &particles::impact(),
+                    );
+                }
+                was_bumping = bumped;
+                particles.update(dt);
+
+                // recorrido: un punto cada cuarto de tile
+                let here = (level.current, player.pos.x / block_size as f32, player.pos.y / block_size as f32);
+                if trail.last().is_none_or(|&(s, x, y)| s != here.0 || (here.1 - x).hypot(here.2 - y) >= 0.25) {
+                    trail.push(here);
+                }
+
+                if rl.is_key_pressed(KeyboardKey::KEY_H) {
+                    show_hint = !show_hint;
+                    used_hint |= show_hint;
+                }
+
+                // tiza: C = flecha a la derecha, V = a la izquierda, en la pared de enfrente
+                let chalk_right = rl.is_key_pressed(KeyboardKey::KEY_C);
+                if chalk_right || rl.is_key_pressed(KeyboardKey::KEY_V) {
+                    let hit = cast_ray(&mut framebuffer, level.maze(), &player, player.a, block_size, &level.here().portals, false);
+                    if hit.impact != Tile::Floor && hit.distance < block_size as f32 * 1.5 {
+                        let (hx, hy) = (hit.hit_x, hit.hit_y);
+                        let (face, _) = hit_face(hx, hy, block_size);
+                        level.here_mut().decals.paint_chalk(
+                            hx as usize / block_size, hy as usize / block_size, face,
+                            Decal { tex: chalk_arrow.clone(), rect: DEFAULT_RECT, flip_u: !chalk_right, chalk: false },
+                        );
+                    }
+                }
 
-                // ¿llegó a la meta 'g'?
-                let i = (player.pos.x as usize) / block_size;
-                let j = (player.pos.y as usize) / block_size;
-                if j < maze.len() && i < maze[0].len() && maze[j][i] == 'g' {
+                // ¿llegó a la meta? (en el modo sin fin, directo al nivel siguiente)
+                if level.maze().at_world(player.pos.x, player.pos.y, block_size) == Some(Tile::Goal) {
+                    if let Some(r) = &mut run {
+                        r.advance(level_time);
+                        start = Some(r.level_seed());
+                        notice = Some((format!("Profundidad {}", r.depth), 2.0));
+                    } else {
+                        win_time = Some(level_time);
+                        new_record = best_times.submit(&record_key(&levels[selected_level], level.seed), level_time);
+                        daily_result = (selected_level == daily_index).then(|| {
+                            let optimal = storeys::solve(&level.mazes).map_or(0, |r| r.len() - 1);
+                            result_line(today, level_time, walked_tiles(&trail), optimal, used_hint)
+                        });
+                        if let Some(line) = &daily_result { println!("{line}"); }
+                        state = GameState::Win;
+                    }
+                }
+
+                // Q: terminar la carrera (cuenta el nivel a medias)
+                if let Some(r) = &run && rl.is_key_pressed(KeyboardKey::KEY_Q) {
+                    run_record = run_log.submit(r.seed, r.depth, r.total + level_time);
                     win_time = Some(level_time);
-                    state = GameState::Win;
+                    state = GameState::RunOver;
                 }
             }
             GameState::Win => {
@@ -309,53 +1258,213 @@ fn main() {
                 if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
                     state = GameState::Title;
                 }
-                // Reintentar el MISMO nivel
+                // Reintentar el MISMO nivel (misma semilla)
                 if rl.is_key_pressed(KeyboardKey::KEY_R) {
-                    let (mz, mut spr, obs) = build_level(LEVELS[selected_level], block_size);
-                    for s in spr.iter_mut() { s.anim = crate_anim.clone(); }
-                    maze = mz;
-                    sprites_world = spr;
-                    obstacles = obs;
-
-                    place_player_at_start(&mut player, &maze, block_size);
-                    level_time = 0.0;
-                    win_time = None;
-                    state = GameState::Playing;
+                    start = Some(level.seed);
+                }
+                // Copiar el resultado del desafío diario
+                if rl.is_key_pressed(KeyboardKey::KEY_C) && let Some(line) = &daily_result {
+                    let msg = if rl.set_clipboard_text(line).is_ok() { "Resultado copiado" } else { "No se pudo copiar" };
+                    notice = Some((msg.to_string(), 3.0));
+                }
+                // Copiar el código del nivel
+                if rl.is_key_pressed(KeyboardKey::KEY_K) && let Some(code) = &share_code {
+                    let msg = if rl.set_clipboard_text(code).is_ok() { "Código copiado" } else { "No se pudo copiar" };
+                    notice = Some((msg.to_string(), 3.0));
+                }
+            }
+            GameState::RunOver => {
+                if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    run = None;
+                    state = GameState::Title;
+                }
+                // Repetir la carrera desde el principio (misma semilla)
+                if rl.is_key_pressed(KeyboardKey::KEY_R) && let Some(r) = &mut run {
+                    *r = EndlessRun::new(r.seed);
+                    start = Some(r.level_seed());
                 }
             }
         }
 
+        // exportar el plano del nivel actual
+        if state != GameState::Title && rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            let msg = match export_level(&level, current_def(&levels, selected_level, run.as_ref()), &trail, block_size) {
+                Ok(base) => format!("Exportado: {base}.{{txt,png,svg,lvlb}}"),
+                Err(e)   => format!("Error al exportar: {e}"),
+            };
+            eprintln!("{msg}");
+            notice = Some((msg, 4.0));
+        }
+        if let Some((_, t)) = &mut notice {
+            *t -= rl.get_frame_time();
+            if *t <= 0.0 { notice = None; }
+        }
+
+        if let Some(seed) = start {
+            let def = current_def(&levels, selected_level, run.as_ref());
+            level = build_level(def, block_size, seed, &atlas, &mut textures, &mut decal_lib);
+            share_code = level_code(def, seed);
+            place_player_at_start(&mut player, &mut level, block_size);
+            player.a = def.start_angle.unwrap_or(std::f32::consts::PI / 4.0);
+            dust_emitter = spawn_level_emitters(&mut particles, &level, def, block_size);
+            transition = None;
+            level_time = 0.0;
+            win_time = None;
+            used_hint = false;
+            trail.clear();
+            state = GameState::Playing;
+        }
+
         // --- Dibujo ---
         let mut d = rl.begin_drawing(&thread);
 
         match state {
             GameState::Title => {
-                draw_title_screen(&mut d, screen_w, screen_h, selected_level);
+                draw_title_screen(
+                    &mut d, screen_w, screen_h, &levels, &best_times, run_log.best(),
+                    selected_level, &seed_input, editing_seed, daily_index, &load_errors,
+                );
             }
             GameState::Playing => {
                 framebuffer.clear(Color::BLACK);
 
                 zbuffer.fill(f32::INFINITY);
-                render3d(&mut framebuffer, &maze, &player, block_size, &atlas, &mut zbuffer);
+                let storey = level.here();
+                render3d(
+                    &mut framebuffer, level.maze(), &player, block_size,
+                    &level.atlas, &storey.decals, &storey.portals, &storey.atmo, &mut zbuffer,
+                    &mut seen_rays,
+                );
 
-                render_sprites(&mut framebuffer, &player, &sprites_world, &zbuffer, player.fov, level_time);
+                sprite_depth.fill(f32::INFINITY);
+                render_sprites(
+                    &mut framebuffer, &player, &storey.sprites,
+                    &zbuffer, &seen_rays, &mut sprite_depth,
+                    player.fov, level_time, &storey.atmo,
+                    block_size as f32 * 0.5,
+                );
+                particles.render(
+                    &mut framebuffer, &player, &zbuffer, &sprite_depth,
+                    player.fov, &storey.atmo,
+                    block_size as f32 * 0.5,
+                );
 
                 framebuffer.draw(&mut d);
+                let here = (player.pos.x as usize / block_size, player.pos.y as usize / block_size);
+                let hint = show_hint.then(|| hint_route(&level, here)).flatten();
                 draw_minimap(
                     &mut d,
-                    &maze,
+                    level.maze(),
                     &player,
                     block_size,
                     screen_w, screen_h,
-                    MiniMapOpts { tile: 6, margin: 10, corner: Corner::TopRight }
+                    MiniMapOpts { tile: 6, margin: 10, corner: Corner::TopRight },
+                    hint.as_ref().map(|(route, _)| route),
+                    (level.current, level.mazes.len()),
                 );
 
                 d.draw_fps(10, 10);
-                d.draw_text("Izq/Der giran, Arr/Ab avanzan", 10, 40, 20, Color::WHITE);
+                d.draw_text("Izq/Der giran, Arr/Ab avanzan, C/V tiza, H pista, F5 exportar", 10, 40, 20, Color::WHITE);
+                d.draw_text(
+                    &format!("{}   Semilla: {}   Bucles: {}   Ruta: {}",
+                        current_def(&levels, selected_level, run.as_ref()).name, level.seed, level.report.loops_added, level.report.path_len),
+                    10, 65, 20, Color::LIGHTGRAY,
+                );
+                let mut y = 90;
+                if let Some(r) = &run {
+                    d.draw_text(
+                        &format!("Sin fin: profundidad {}   Tiempo total {:.1} s   (Q termina)", r.depth, r.total + level_time),
+                        10, y, 20, Color::SKYBLUE,
+                    );
+                    y += 25;
+                }
+                if let Some((_, steps)) = &hint {
+                    d.draw_text(&format!("Meta a {steps} pasos"), 10, y, 20, Color::ORANGE);
+                }
+
+                // fundido al cambiar de piso: oscurece hasta la mitad y vuelve
+                if let Some((t, _)) = transition {
+                    let half = STOREY_FADE / 2.0;
+                    let alpha = (1.0 - (t - half).abs() / half).clamp(0.0, 1.0);
+                    d.draw_rectangle(0, 0, screen_w, screen_h, Color::new(0, 0, 0, (alpha * 255.0) as u8));
+                }
             }
             GameState::Win => {
                 let t = win_time.unwrap_or(level_time);
-                draw_win_screen(&mut d, screen_w, screen_h, t);
+                let lv = &levels[selected_level];
+                draw_win_screen(&mut d, screen_w, screen_h, t, lv.par, best_times.get(&record_key(lv, level.seed)), new_record, level.seed, daily_result.as_deref(), share_code.as_deref());
+            }
+            GameState::RunOver => {
+                if let Some(r) = &run {
+                    let total = r.total + win_time.unwrap_or(level_time);
+                    draw_run_screen(&mut d, screen_w, screen_h, r, total, run_log.best(), run_record);
+                }
+            }
+        }
+        if let Some((msg, _)) = &notice {
+            d.draw_text(msg, 10, screen_h - 30, 20, Color::YELLOW);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::{Algorithm, TreeBias};
+    use crate::maze::Placement;
+    use crate::storeys::Link;
+
+    fn spec(generator: Generator, placement: Placement, braid: u8, storeys: usize, link: Link, extras: (usize, usize)) -> LevelDef {
+        let mut def = LevelDef::new("prueba", PathBuf::new());
+        def.source = LevelSource::Procedural(ProcSpec {
+            generator,
+            cells: (7, 5),
+            seed: None,
+            braid,
+            mirrors: extras.0,
+            portal_pairs: extras.1,
+            placement,
+            storeys,
+            link,
+            difficulty: None,
+            walls: WallPaint::default(),
+        });
+        def
+    }
+
+    /// Todo lo que se puede pedir en un nivel procedural sale con solución.
+    #[test]
+    fn generated_levels_are_solvable() {
+        let mut generators: Vec<Generator> = [
+            Algorithm::Backtracker, Algorithm::Prim, Algorithm::Kruskal, Algorithm::Wilson,
+            Algorithm::Eller, Algorithm::BinaryTree, Algorithm::Division,
+        ].into_iter().map(Generator::Maze).collect();
+        for bias in [TreeBias::Newest, TreeBias::Oldest, TreeBias::Random, TreeBias::Middle, TreeBias::Mixed(50)] {
+            generators.push(Generator::Maze(Algorithm::GrowingTree(bias)));
+        }
+        generators.push(Generator::Rooms(Default::default()));
+        let placements = [
+            Placement::Fixed, Placement::FarthestFromStart, Placement::Diameter,
+            Placement::RandomMinPath(0), Placement::RandomMinPath(6), Placement::RandomMinPath(1000),
+        ];
+
+        for &generator in &generators {
+            for placement in placements {
+                for braid in [0, 50, 100] {
+                    for (storeys, link) in [(1, Link::Stairs), (2, Link::Stairs), (3, Link::Elevator)] {
+                        for extras in [(0, 0), (3, 2)] {
+                            let def = spec(generator, placement, braid, storeys, link, extras);
+                            for seed in 0..6 {
+                                let (mazes, _, _) = generate_level(&def, seed);
+                                assert_eq!(mazes.len(), storeys);
+                                assert!(
+                                    storeys::solve(&mazes).is_some(),
+                                    "sin solución: {generator:?} {placement:?} braid {braid} pisos {storeys} extras {extras:?} semilla {seed}",
+                                );
+                            }
+                        }
+                    }
+                }
             }
         }
     }
@@ -1,93 +1,313 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use rand::{thread_rng, Rng};
-use rand::seq::SliceRandom;
-
-pub type Maze = Vec<Vec<char>>;
-
-/// Carga un laberinto desde un fichero de texto, cada línea
-/// es un `Vec<char>`.
-pub fn load_maze(filename: &str) -> Maze {
-    let file   = File::open(filename).expect("No se pudo abrir el archivo");
-    let reader = BufReader::new(file);
-    reader
-        .lines()
-        .map(|l| l.expect("Error leyendo línea").chars().collect())
-        .collect()
+use std::fmt;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::generators::{braid, Carver, MazeGenerator};
+use crate::grid::Grid;
+use crate::painters::{paint_walls, WallPaint};
+use crate::solver::{distance_field, Cell};
+use crate::storeys::{self, destination};
+use crate::tile::Tile;
+
+/// El mapa de un nivel (ver `Grid` y `Tile`).
+pub type Maze = Grid;
+
+/// Dónde colocar el inicio 'p' y la meta 'g'.
+#[derive(Clone, Copy, Default, Debug)]
+pub enum Placement {
+    /// 'p' en la esquina superior izquierda, 'g' en la opuesta.
+    #[default]
+    Fixed,
+    /// 'p' en la esquina; 'g' en la celda alcanzable más lejana.
+    FarthestFromStart,
+    /// Extremos del camino más largo (diámetro; exacto en laberintos perfectos).
+    Diameter,
+    /// Inicio al azar y meta al azar a al menos N celdas de recorrido.
+    RandomMinPath(usize),
+}
+
+/// Post-procesos opcionales de la generación.
+#[derive(Clone, Copy, Default)]
+pub struct MazeOptions {
+    pub braid: u8,            // % de callejones sin salida a eliminar (0 = laberinto perfecto)
+    pub placement: Placement, // estrategia para 'p' y 'g'
+}
+
+/// Lo que hizo la generación, para mostrarlo o registrarlo.
+#[derive(Clone, Copy, Default)]
+pub struct MazeReport {
+    pub loops_added: usize,
+    pub path_len: usize, // celdas de recorrido entre 'p' y 'g'
+}
+
+/// Error al cargar un laberinto. `line` y `col` empiezan en 1 y se refieren
+/// al archivo original (las directivas '@' cuentan como líneas).
+#[derive(Debug)]
+pub enum LoadError {
+    Empty,
+    Ragged { line: usize, expected: usize, found: usize },
+    UnknownTile { line: usize, col: usize, tile: char },
+    OpenBorder { line: usize, col: usize },
+    NoStart,
+    ExtraStart { line: usize, col: usize },
+    NoGoal,
+    Unsolvable,
+    StoreySize { line: usize, storey: usize },
+    DeadLink { line: usize, col: usize },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Empty => write!(f, "el laberinto está vacío"),
+            LoadError::Ragged { line, expected, found } =>
+                write!(f, "línea {line}: ancho {found}, se esperaba {expected}"),
+            LoadError::UnknownTile { line, col, tile } =>
+                write!(f, "línea {line}, columna {col}: tile desconocido '{tile}'"),
+            LoadError::OpenBorder { line, col } =>
+                write!(f, "línea {line}, columna {col}: el borde debe ser muro"),
+            LoadError::NoStart => write!(f, "falta el inicio 'p'"),
+            LoadError::ExtraStart { line, col } =>
+                write!(f, "línea {line}, columna {col}: segundo inicio 'p'"),
+            LoadError::NoGoal => write!(f, "falta la meta 'g'"),
+            LoadError::Unsolvable => write!(f, "no hay camino de 'p' a ninguna 'g'"),
+            LoadError::StoreySize { line, storey } =>
+                write!(f, "línea {line}: el piso {storey} no mide lo mismo que el primero"),
+            LoadError::DeadLink { line, col } =>
+                write!(f, "línea {line}, columna {col}: la escalera o ascensor no lleva a ningún piso"),
+        }
+    }
 }
 
-/// Genera un laberinto procedural con DFS en un grid de celdas.
-/// El resultado es una malla de caracteres donde:
-/// - '#' son muros  
-/// - ' ' (espacio) suelo  
-/// - 'p' posición inicial del jugador  
-/// - 'g' meta
-pub fn make_maze(cell_w: usize, cell_h: usize) -> Maze {
-    let gw = cell_w * 2 + 1;
-    let gh = cell_h * 2 + 1;
-
-    // Inicializa todo como muros
-    let mut maze    = vec![vec!['#'; gw]; gh];
-    let mut visited = vec![vec![false; cell_w]; cell_h];
-    let mut rng     = thread_rng();
-
-    fn carve(
-        cx: usize, cy: usize, cw: usize, ch: usize,
-        maze: &mut Maze, visited: &mut Vec<Vec<bool>>, rng: &mut impl Rng,
-    ) {
-        visited[cy][cx] = true;
-        let mut dirs = vec![(0isize, -1isize), (1, 0), (0, 1), (-1, 0)];
-        dirs.shuffle(rng);
-
-        for &(dx, dy) in &dirs {
-            let nx = cx as isize + dx;
-            let ny = cy as isize + dy;
-            if nx < 0 || nx >= cw as isize || ny < 0 || ny >= ch as isize { continue; }
-            let (nx, ny) = (nx as usize, ny as usize);
-            if visited[ny][nx] { continue; }
-
-            let x1 = cx * 2 + 1;
-            let y1 = cy * 2 + 1;
-            let x2 = nx * 2 + 1;
-            let y2 = ny * 2 + 1;
-
-            maze[y1][x1] = ' ';
-            maze[y2][x2] = ' ';
-            maze[(y1 + y2) / 2][(x1 + x2) / 2] = ' ';
-
-            carve(nx, ny, cw, ch, maze, visited, rng);
+impl std::error::Error for LoadError {}
+
+/// Lee los pisos de un laberinto de texto, una fila por línea. Las líneas
+/// que empiezan con '@' son directivas (p. ej. `@decal`) y, como las vacías,
+/// no forman parte del grid; `@storey` empieza el piso de arriba.
+///
+/// Comprueba que las filas tengan el mismo ancho, que todos los pisos midan
+/// lo mismo, que el borde sea muro, que todos los tiles sean conocidos, que
+/// cada escalera o ascensor lleve a algún piso y que haya exactamente un 'p'
+/// y al menos una 'g' (en cualquier piso). Con `require_solvable` además
+/// exige un camino a pie (y por escaleras) de 'p' a alguna 'g' (los portales
+/// no cuentan como atajo).
+pub fn parse_storeys(text: &str, require_solvable: bool) -> Result<Vec<Maze>, LoadError> {
+    let mut storeys = Vec::new();
+    let mut lines: Vec<Vec<usize>> = Vec::new();
+    let mut rows: Vec<Vec<Tile>> = Vec::new();
+    let mut rows_lines = Vec::new();
+    for (n, l) in text.lines().enumerate() {
+        let line = n + 1;
+        if l.split_whitespace().next() == Some("@storey") {
+            storeys.push(Grid::from_rows(std::mem::take(&mut rows)));
+            lines.push(std::mem::take(&mut rows_lines));
+            rows_lines.push(line); // el piso vacío se reporta en su @storey
+            continue;
+        }
+        if l.is_empty() || l.starts_with('@') { continue; }
+        let width = l.chars().count();
+        if let Some(expected) = rows.first().map(|r| r.len()).filter(|&w| w != width) {
+            return Err(LoadError::Ragged { line, expected, found: width });
         }
+        let row = l
+            .chars()
+            .enumerate()
+            .map(|(i, c)| Tile::from_char(c).ok_or(LoadError::UnknownTile { line, col: i + 1, tile: c }))
+            .collect::<Result<_, _>>()?;
+        if rows.is_empty() { rows_lines.clear(); }
+        rows.push(row);
+        rows_lines.push(line);
     }
+    storeys.push(Grid::from_rows(rows));
+    lines.push(rows_lines);
+    check_storeys(&storeys, &lines, require_solvable)?;
+    Ok(storeys)
+}
+
+/// Las mismas comprobaciones que `parse_storeys` sobre un grid ya construido
+/// (p. ej. importado de una imagen); la fila `j` se reporta como línea `j + 1`.
+pub fn validate_maze(maze: &Maze, require_solvable: bool) -> Result<(), LoadError> {
+    validate_storeys(std::slice::from_ref(maze), require_solvable)
+}
+
+/// Lo mismo para todos los pisos de un nivel.
+pub fn validate_storeys(storeys: &[Maze], require_solvable: bool) -> Result<(), LoadError> {
+    let lines: Vec<Vec<usize>> = storeys.iter().map(|m| (1..=m.height()).collect()).collect();
+    check_storeys(storeys, &lines, require_solvable)
+}
 
-    // Generar
-    carve(0, 0, cell_w, cell_h, &mut maze, &mut visited, &mut rng);
+/// `lines[s][j]` = número de línea de la fila `j` del piso `s` en el archivo
+/// original.
+fn check_storeys(storeys: &[Maze], lines: &[Vec<usize>], require_solvable: bool) -> Result<(), LoadError> {
+    let Some(first) = storeys.first() else { return Err(LoadError::Empty) };
+    let (w, h) = (first.width(), first.height());
+    if w == 0 || h == 0 { return Err(LoadError::Empty); }
+
+    let mut start = None;
+    let mut goals = 0;
+    for (s, maze) in storeys.iter().enumerate() {
+        if (maze.width(), maze.height()) != (w, h) {
+            let line = lines[s].first().copied().unwrap_or(0);
+            return Err(LoadError::StoreySize { line, storey: s + 1 });
+        }
+        for ((i, j), t) in maze.cells() {
+            let (line, col) = (lines[s][j], i + 1);
+            let border = i == 0 || j == 0 || i == w - 1 || j == h - 1;
+            if border && !t.is_solid() {
+                return Err(LoadError::OpenBorder { line, col });
+            }
+            if t.is_storey_link() && destination(storeys, s, (i, j)).is_none() {
+                return Err(LoadError::DeadLink { line, col });
+            }
+            match t {
+                Tile::Start if start.is_some() => return Err(LoadError::ExtraStart { line, col }),
+                Tile::Start => start = Some((s, (i, j))),
+                Tile::Goal => goals += 1,
+                _ => {}
+            }
+        }
+    }
+    if start.is_none() { return Err(LoadError::NoStart); }
+    if goals == 0 { return Err(LoadError::NoGoal); }
+
+    if require_solvable && storeys::solve(storeys).is_none() {
+        return Err(LoadError::Unsolvable);
+    }
+    Ok(())
+}
+
+/// Genera un laberinto procedural en un grid de celdas con el algoritmo y
+/// opciones indicados. El resultado es un `Grid` de muros y suelo con la
+/// posición inicial (`Tile::Start`) y la meta (`Tile::Goal`).
+///
+/// La misma `seed` produce siempre el mismo laberinto.
+pub fn make_maze_with(
+    generator: &dyn MazeGenerator,
+    cell_w: usize,
+    cell_h: usize,
+    seed: u64,
+    opts: MazeOptions,
+) -> (Maze, MazeReport) {
+    let (carver, mut report) = generate(generator, cell_w, cell_h, seed, opts);
+    let mut maze = carver.to_maze();
 
     // Posición inicial y meta
-    maze[1][1]           = 'p';
-    maze[gh - 2][gw - 2] = 'g';
+    let mut rng = StdRng::seed_from_u64(seed.wrapping_add(2));
+    let (start, goal, steps) = place_endpoints(&maze, opts.placement, &mut rng);
+    maze[start] = Tile::Start;
+    maze[goal]  = Tile::Goal;
+    report.path_len = steps as usize / 2;
 
-    // >>> Post-proceso: reasignar algunos muros '#' a 'A' (patrón determinista)
+    // >>> Post-proceso: reasignar algunos muros a piedra (patrón determinista)
     retile_walls_stripes(&mut maze);
 
-    maze
+    (maze, report)
 }
 
-/// Convierte parte de los muros '#' a 'A' con un patrón tipo franjas.
-/// No toca bordes, ni 'p'/'g', ni espacios.
-fn retile_walls_stripes(maze: &mut Maze) {
-    let h = maze.len();
-    if h == 0 { return; }
-    let w = maze[0].len();
-
-    for j in 1..h - 1 {
-        for i in 1..w - 1 {
-            if maze[j][i] == '#' {
-                // Ensancha la franja usando i/2 y j/2; 
-                if ((i / 2) + (j / 2)) % 3 == 0 {
-                    maze[j][i] = 'A'; 
+/// Genera solo la estructura compacta (sin 'p'/'g' ni retexturizado), para
+/// laberintos enormes que no conviene expandir a caracteres de una vez.
+pub fn generate(
+    generator: &dyn MazeGenerator,
+    cell_w: usize,
+    cell_h: usize,
+    seed: u64,
+    opts: MazeOptions,
+) -> (Carver, MazeReport) {
+    let mut carver = Carver::new(cell_w, cell_h);
+    let mut rng    = StdRng::seed_from_u64(seed);
+    generator.carve(&mut carver, &mut rng);
+    let loops_added = braid(&mut carver, opts.braid, &mut rng);
+    (carver, MazeReport { loops_added, ..Default::default() })
+}
+
+/// Tile del centro de la celda (x, y).
+fn cell_tile(x: usize, y: usize) -> Cell {
+    (2 * x + 1, 2 * y + 1)
+}
+
+/// Elige (inicio, meta, pasos en tiles) según la estrategia. Solo se usan
+/// centros de celda, así que los pasos son siempre el doble de las celdas.
+fn place_endpoints(maze: &Maze, placement: Placement, rng: &mut StdRng) -> (Cell, Cell, u32) {
+    let cw = maze.width() / 2;
+    let ch = maze.height() / 2;
+    let corner = cell_tile(0, 0);
+
+    // Centro de celda más lejano desde `from`
+    let farthest = |from: Cell| {
+        let field = distance_field(maze, from);
+        (0..ch)
+            .flat_map(|y| (0..cw).map(move |x| cell_tile(x, y)))
+            .filter_map(|c| field.get(c).map(|d| (d, c)))
+            .max_by_key(|&(d, c)| (d, std::cmp::Reverse(c)))
+            .map_or((from, 0), |(d, c)| (c, d))
+    };
+
+    match placement {
+        Placement::Fixed => {
+            let goal = cell_tile(cw - 1, ch - 1);
+            let steps = distance_field(maze, corner).get(goal).unwrap_or(0);
+            (corner, goal, steps)
+        }
+        Placement::FarthestFromStart => {
+            let (goal, steps) = farthest(corner);
+            (corner, goal, steps)
+        }
+        Placement::Diameter => {
+            let (start, _) = farthest(corner);
+            let (goal, steps) = farthest(start);
+            (start, goal, steps)
+        }
+        Placement::RandomMinPath(min_cells) => {
+            const TRIES: usize = 16;
+            // al menos una celda de distancia: la meta nunca pisa el inicio
+            let min_steps = u32::try_from(min_cells).map_or(u32::MAX, |n| n.saturating_mul(2)).max(2);
+            for _ in 0..TRIES {
+                let start = cell_tile(rng.gen_range(0..cw), rng.gen_range(0..ch));
+                let field = distance_field(maze, start);
+                let far: Vec<(Cell, u32)> = (0..ch)
+                    .flat_map(|y| (0..cw).map(move |x| cell_tile(x, y)))
+                    .filter(|&c| c != start)
+                    .filter_map(|c| field.get(c).filter(|&d| d >= min_steps).map(|d| (c, d)))
+                    .collect();
+                if !far.is_empty() {
+                    let (goal, steps) = far[rng.gen_range(0..far.len())];
+                    return (start, goal, steps);
                 }
             }
+            // Laberinto demasiado pequeño para el mínimo: lo más largo posible
+            place_endpoints(maze, Placement::Diameter, rng)
         }
     }
 }
 
+/// Convierte parte de los muros (`Wall`) a `Stone` con un patrón tipo franjas
+/// (`Painter::Stripes` con sus materiales por defecto). No toca bordes ni
+/// casillas transitables.
+pub fn retile_walls_stripes(maze: &mut Maze) {
+    paint_walls(maze, &WallPaint::default(), 0, &[], (0, 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::Backtracker;
+
+    /// Ni en los mapas más chicos la meta pisa el inicio.
+    #[test]
+    fn start_and_goal_never_overlap() {
+        let placements = [
+            Placement::Fixed, Placement::FarthestFromStart, Placement::Diameter,
+            Placement::RandomMinPath(0), Placement::RandomMinPath(1), Placement::RandomMinPath(usize::MAX),
+        ];
+        for (cw, ch) in [(1, 2), (2, 1), (2, 2), (3, 1)] {
+            for placement in placements {
+                for seed in 0..20 {
+                    let opts = MazeOptions { braid: 0, placement };
+                    let (maze, report) = make_maze_with(&Backtracker, cw, ch, seed, opts);
+                    let count = |t: Tile| maze.cells().filter(|&(_, c)| c == t).count();
+                    assert_eq!((count(Tile::Start), count(Tile::Goal)), (1, 1), "{cw}x{ch} {placement:?} semilla {seed}");
+                    assert!(report.path_len >= 1);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+// src/metrics.rs
+use std::fmt::Write;
+use crate::maze::Maze;
+use crate::solver::{distance_field, Cell};
+use crate::storeys;
+use crate::tile::Tile;
+
+/// Métricas de dificultad de un mapa. Las distancias van en tiles (un paso =
+/// un tile); las de la solución quedan vacías si no hay camino de 'p' a 'g'.
+#[derive(Clone, Debug, Default)]
+pub struct MazeMetrics {
+    pub size: (usize, usize),
+    pub floor: usize,             // casillas transitables
+    pub solution: Option<usize>,  // pasos del camino más corto de 'p' a 'g'
+    pub dead_ends: usize,         // casillas con una sola salida (sin contar 'p', 'g' ni escaleras)
+    pub junctions: usize,         // casillas con 3 o 4 salidas, fuera de salas abiertas
+    pub decisions: usize,         // cruces por los que pasa la solución
+    pub branching: f32,           // caminos nuevos por cruce (salidas - 1, en promedio)
+    pub river: f32,               // tiles por callejón o cruce: alto = pasillos largos
+    pub longest_dead_end: usize,  // tiles del callejón más largo hasta su cruce
+    pub loops: usize,             // ciclos alrededor de algún muro
+    pub off_path: Vec<Cell>,      // alcanzables desde 'p' y fuera de todo camino más corto
+    pub effort: f32,              // ver `effort`
+    pub score: f32,               // 0..100
+}
+
+fn exits(maze: &Maze, (i, j): Cell) -> impl Iterator<Item = Cell> + '_ {
+    [(1isize, 0isize), (0, 1), (-1, 0), (0, -1)].into_iter().filter_map(move |(dx, dy)| {
+        let c = (i.checked_add_signed(dx)?, j.checked_add_signed(dy)?);
+        maze.get(c.0, c.1)?.is_walkable().then_some(c)
+    })
+}
+
+/// Analiza un mapa (un piso) de 'p' a la meta más cercana.
+pub fn analyze(maze: &Maze) -> MazeMetrics {
+    let goals: Vec<Cell> = maze.cells().filter(|&(_, t)| t == Tile::Goal).map(|(c, _)| c).collect();
+    measure(maze, maze.find(Tile::Start), &goals)
+}
+
+/// Analiza cada piso de un nivel; en los pisos por los que pasa la solución
+/// los extremos son donde ella entra y sale (escaleras, ascensores, 'p', 'g').
+pub fn analyze_storeys(mazes: &[Maze]) -> Vec<MazeMetrics> {
+    let route = storeys::solve(mazes).unwrap_or_default();
+    mazes.iter().enumerate().map(|(s, maze)| {
+        let mut here = route.iter().filter(|&&(k, _)| k == s).map(|&(_, c)| c);
+        match (here.next(), here.next_back()) {
+            (Some(a), b) => measure(maze, Some(a), &[b.unwrap_or(a)]),
+            _ => analyze(maze),
+        }
+    }).collect()
+}
+
+fn measure(maze: &Maze, start: Option<Cell>, goals: &[Cell]) -> MazeMetrics {
+    let (w, h) = (maze.width(), maze.height());
+    let walkable: Vec<Cell> = maze.cells().filter(|&(_, t)| t.is_walkable()).map(|(c, _)| c).collect();
+    let degree = |c: Cell| exits(maze, c).count();
+    let open = |i: usize, j: usize| maze.get(i, j).is_some_and(Tile::is_walkable);
+    let block = |i: usize, j: usize| open(i, j) && open(i + 1, j) && open(i, j + 1) && open(i + 1, j + 1);
+    // En una sala casi todo tiene 3 o 4 salidas; esas casillas no son cruces
+    let in_room = |(i, j): Cell| (0..2).any(|a| (0..2).any(|b| i >= a && j >= b && block(i - a, j - b)));
+    let junction = |c: Cell| degree(c) >= 3 && !in_room(c);
+    let mut m = MazeMetrics { size: (w, h), floor: walkable.len(), ..Default::default() };
+
+    // Callejones y cruces
+    let special = |c: Cell| matches!(maze[c], Tile::Start | Tile::Goal) || maze[c].is_storey_link();
+    let mut ways = 0;
+    for &c in &walkable {
+        if junction(c) {
+            m.junctions += 1;
+            ways += degree(c) - 1;
+        } else if degree(c) == 1 && !special(c) {
+            m.dead_ends += 1;
+            m.longest_dead_end = m.longest_dead_end.max(dead_end_length(maze, c));
+        }
+    }
+    if m.junctions > 0 { m.branching = ways as f32 / m.junctions as f32; }
+    m.river = m.floor as f32 / (m.dead_ends + m.junctions).max(1) as f32;
+
+    // Ciclos: E - V + C cuenta las caras del grafo; se descuentan los bloques
+    // 2x2 abiertos, que son "ciclos" de sala y no rodean ningún muro.
+    let edges: usize = walkable.iter().map(|&(i, j)| open(i + 1, j) as usize + open(i, j + 1) as usize).sum();
+    let blocks = walkable.iter().filter(|&&(i, j)| block(i, j)).count();
+    let faces = edges + components(maze, &walkable) - walkable.len();
+    m.loops = faces - blocks;
+
+    // Solución y casillas que ningún camino más corto usa
+    if let Some(start) = start {
+        let from_start = distance_field(maze, start);
+        let goal = goals.iter()
+            .filter_map(|&c| from_start.get(c).map(|d| (d, c)))
+            .min();
+        if let Some((len, goal)) = goal {
+            let from_goal = distance_field(maze, goal);
+            let mut on_path = Vec::new();
+            for &c in &walkable {
+                let (Some(a), Some(b)) = (from_start.get(c), from_goal.get(c)) else { continue };
+                if a + b == len { on_path.push(c) } else { m.off_path.push(c) }
+            }
+            m.solution = Some(len as usize);
+            m.decisions = on_path.iter().filter(|&&c| c != goal && junction(c)).count();
+        }
+    }
+    m.effort = effort(&m);
+    m.score = score(m.effort);
+    m
+}
+
+/// Tiles desde un callejón hasta el primer cruce (o hasta otro callejón).
+fn dead_end_length(maze: &Maze, from: Cell) -> usize {
+    let (mut prev, mut cur, mut len) = (from, from, 1);
+    loop {
+        let mut next = exits(maze, cur).filter(|&n| n != prev);
+        let (Some(n), None) = (next.next(), next.next()) else { return len };
+        if exits(maze, n).count() != 2 { return len; }
+        (prev, cur, len) = (cur, n, len + 1);
+    }
+}
+
+/// Zonas transitables separadas entre sí.
+fn components(maze: &Maze, walkable: &[Cell]) -> usize {
+    let w = maze.width();
+    let mut seen = vec![false; w * maze.height()];
+    let mut count = 0;
+    for &c in walkable {
+        if seen[c.1 * w + c.0] { continue; }
+        count += 1;
+        seen[c.1 * w + c.0] = true;
+        let mut stack = vec![c];
+        while let Some(c) = stack.pop() {
+            for n in exits(maze, c) {
+                if !seen[n.1 * w + n.0] {
+                    seen[n.1 * w + n.0] = true;
+                    stack.push(n);
+                }
+            }
+        }
+    }
+    count
+}
+
+/// Esfuerzo estimado para resolver el mapa: recorrer la solución (un punto
+/// cada 10 pasos), elegir en cada cruce y perderse por lo que queda fuera
+/// de ella (un punto cada 10 casillas). Crece sin límite con el tamaño.
+fn effort(m: &MazeMetrics) -> f32 {
+    let Some(len) = m.solution else { return 0.0 };
+    len as f32 / 10.0 + m.decisions as f32 + m.off_path.len() as f32 / 10.0
+}
+
+/// Esfuerzo -> puntaje 0..100 (un laberinto de 16x12 celdas ronda los 50-65).
+fn score(effort: f32) -> f32 {
+    100.0 * (1.0 - (-effort / EFFORT_SCALE).exp())
+}
+
+const EFFORT_SCALE: f32 = 60.0;
+
+/// Puntaje de un nivel de varios pisos: se suma el esfuerzo de cada uno.
+pub fn level_score(storeys: &[MazeMetrics]) -> f32 {
+    score(storeys.iter().map(|m| m.effort).sum())
+}
+
+/// Informe legible del nivel (un bloque por piso).
+pub fn report_text(name: &str, seed: u64, storeys: &[MazeMetrics]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Nivel: {name} (semilla {seed})");
+    let _ = writeln!(out, "Dificultad: {:.1}/100", level_score(storeys));
+    for (s, m) in storeys.iter().enumerate() {
+        let _ = writeln!(out);
+        if storeys.len() > 1 { let _ = writeln!(out, "Piso {}", s + 1); }
+        let _ = writeln!(out, "  Tamaño: {}x{} tiles, {} transitables", m.size.0, m.size.1, m.floor);
+        match m.solution {
+            Some(len) => { let _ = writeln!(out, "  Solución: {len} pasos (cruces en el camino: {})", m.decisions); }
+            None      => { let _ = writeln!(out, "  Solución: no hay camino"); }
+        }
+        let _ = writeln!(out, "  Callejones: {} (el más largo, {} tiles)", m.dead_ends, m.longest_dead_end);
+        let _ = writeln!(out, "  Cruces: {} (ramificación {:.2})", m.junctions, m.branching);
+        let _ = writeln!(out, "  Factor río: {:.1}", m.river);
+        let _ = writeln!(out, "  Ciclos: {}", m.loops);
+        let pct = 100.0 * m.off_path.len() as f32 / m.floor.max(1) as f32;
+        let _ = writeln!(out, "  Fuera de todo camino más corto: {} ({pct:.0}%)", m.off_path.len());
+        let _ = writeln!(out, "  Puntaje del piso: {:.1}", m.score);
+    }
+    out
+}
+
+/// El mismo informe en JSON (con la lista de casillas fuera del camino).
+pub fn report_json(name: &str, seed: u64, storeys: &[MazeMetrics]) -> String {
+    let mut out = String::new();
+    let _ = write!(out, "{{\"name\":{},\"seed\":{seed},\"score\":{:.2},\"storeys\":[", json_str(name), level_score(storeys));
+    for (s, m) in storeys.iter().enumerate() {
+        if s > 0 { out.push(','); }
+        let solution = m.solution.map_or("null".to_string(), |n| n.to_string());
+        let _ = write!(
+            out,
+            "{{\"width\":{},\"height\":{},\"floor\":{},\"solution\":{solution},\"dead_ends\":{},\"junctions\":{},\
+             \"decisions\":{},\"branching\":{:.3},\"river\":{:.3},\"longest_dead_end\":{},\"loops\":{},\"score\":{:.2},\"off_path\":[",
+            m.size.0, m.size.1, m.floor, m.dead_ends, m.junctions,
+            m.decisions, m.branching, m.river, m.longest_dead_end, m.loops, m.score,
+        );
+        for (k, (i, j)) in m.off_path.iter().enumerate() {
+            let _ = write!(out, "{}[{i},{j}]", if k > 0 { "," } else { "" });
+        }
+        out.push_str("]}");
+    }
+    out.push_str("]}\n");
+    out
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"'  => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => { let _ = write!(out, "\\u{:04x}", c as u32); }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,6 +1,7 @@
 use raylib::prelude::*;
-use crate::{maze::Maze, player::Player};
+use crate::{maze::Maze, player::Player, solver::Route};
 
+#[allow(dead_code)] // el juego usa TopRight; el resto queda para configurarlo
 pub enum Corner { TopLeft, TopRight, BottomLeft, BottomRight }
 
 pub struct MiniMapOpts {
@@ -9,6 +10,7 @@ pub struct MiniMapOpts {
     pub corner: Corner // esquina donde dibujar
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_minimap(
     d: &mut RaylibDrawHandle,
     maze: &Maze,
@@ -17,9 +19,11 @@ pub fn draw_minimap(
     screen_w: i32,
     screen_h: i32,
     opts: MiniMapOpts,
+    hint: Option<&Route>,
+    (storey, storeys): (usize, usize), // piso actual y cuántos hay
 ) {
-    let w_cells = maze[0].len() as i32;
-    let h_cells = maze.len() as i32;
+    let w_cells = maze.width() as i32;
+    let h_cells = maze.height() as i32;
     let map_w = w_cells * opts.tile;
     let map_h = h_cells * opts.tile;
 
@@ -35,18 +39,18 @@ pub fn draw_minimap(
     d.draw_rectangle(x0 - 3, y0 - 3, map_w + 6, map_h + 6, Color::new(0, 0, 0, 160));
 
     // celdas
-    for j in 0..h_cells {
-        for i in 0..w_cells {
-            let c = maze[j as usize][i as usize];
-            let col = match c {
-                '#' => Color::DARKGRAY,
-                'A' => Color::GRAY,
-                'g' => Color::GREEN,
-                'p' => Color::SKYBLUE,
-                ' ' => Color::new(255, 255, 255, 40),
-                _   => Color::new(255, 255, 255, 80),
-            };
-            d.draw_rectangle(x0 + i * opts.tile, y0 + j * opts.tile, opts.tile, opts.tile, col);
+    for ((i, j), t) in maze.cells() {
+        let (i, j) = (i as i32, j as i32);
+        d.draw_rectangle(x0 + i * opts.tile, y0 + j * opts.tile, opts.tile, opts.tile, t.info().minimap);
+    }
+
+    // pista: ruta a la meta
+    if let Some(route) = hint {
+        let center = |(i, j): (usize, usize)| (x0 + i as i32 * opts.tile + opts.tile / 2, y0 + j as i32 * opts.tile + opts.tile / 2);
+        for pair in route.cells.windows(2) {
+            let (ax, ay) = center(pair[0]);
+            let (bx, by) = center(pair[1]);
+            d.draw_line(ax, ay, bx, by, Color::ORANGE);
         }
     }
 
@@ -63,4 +67,9 @@ pub fn draw_minimap(
 
     // borde
     d.draw_rectangle_lines(x0 - 3, y0 - 3, map_w + 6, map_h + 6, Color::WHITE);
+
+    // piso actual, solo si hay más de uno
+    if storeys > 1 {
+        d.draw_text(&format!("Piso {}/{storeys}", storey + 1), x0, y0 + map_h + 8, 20, Color::WHITE);
+    }
 }
@@ -0,0 +1,168 @@
+// src/painters.rs
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use crate::dungeon::Room;
+use crate::maze::Maze;
+use crate::solver::{distance_field, Cell};
+use crate::tile::Tile;
+
+/// Cómo se reparten los materiales de muro por el mapa.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Painter {
+    /// Franjas diagonales fijas ((i/2 + j/2) % n), sin tocar el borde.
+    Stripes,
+    /// Manchas de ruido de valor; `scale` = tiles entre puntos de la red.
+    Noise { scale: f32 },
+    /// Zonas de Voronoi alrededor de `zones` puntos al azar.
+    Voronoi { zones: usize },
+    /// Gradiente según la distancia a pie desde la entrada del piso: el
+    /// primer material cerca del inicio y el último en lo más hondo.
+    Depth,
+    /// Un material por sala (sus muros), y el primero para los pasillos.
+    Rooms,
+}
+
+impl Painter {
+    /// Materiales si el nivel no los da.
+    pub fn default_palette(self) -> Vec<Tile> {
+        match self {
+            Painter::Stripes => vec![Tile::Stone, Tile::Wall, Tile::Wall],
+            Painter::Noise { .. } => vec![Tile::Wall, Tile::Stone, Tile::Moss],
+            Painter::Voronoi { .. } => vec![Tile::Wall, Tile::Stone, Tile::DarkBrick, Tile::Sandstone],
+            Painter::Depth => vec![Tile::Wall, Tile::DarkBrick, Tile::Moss, Tile::DarkStone],
+            Painter::Rooms => vec![Tile::Wall, Tile::Stone, Tile::Sandstone, Tile::Moss, Tile::DarkBrick],
+        }
+    }
+}
+
+/// Pintor y materiales (cualquier cantidad, todos `Tile::is_material`).
+#[derive(Clone, PartialEq, Debug)]
+pub struct WallPaint {
+    pub painter: Painter,
+    pub palette: Vec<Tile>,
+}
+
+impl Default for WallPaint {
+    fn default() -> Self {
+        Self { painter: Painter::Stripes, palette: Painter::Stripes.default_palette() }
+    }
+}
+
+/// Repinta los muros comunes del mapa (los espejos no se tocan). La misma
+/// `seed` da siempre el mismo resultado. `rooms` solo lo usa `Painter::Rooms`
+/// y `(storey, storeys)` lo usa `Painter::Depth` para seguir oscureciendo de
+/// un piso al siguiente.
+pub fn paint_walls(maze: &mut Maze, paint: &WallPaint, seed: u64, rooms: &[Room], (storey, storeys): (usize, usize)) {
+    let (w, h) = (maze.width(), maze.height());
+    let n = paint.palette.len();
+    if n == 0 || w == 0 { return; }
+    let pick = |v: f32| paint.palette[((v * n as f32) as usize).min(n - 1)];
+
+    let material: Box<dyn Fn(Cell) -> Option<Tile>> = match paint.painter {
+        Painter::Stripes => Box::new(|(i, j): Cell| {
+            let inside = i > 0 && j > 0 && i < w - 1 && j < h - 1;
+            inside.then(|| paint.palette[(i / 2 + j / 2) % n])
+        }),
+        Painter::Noise { scale } => {
+            // Por percentil entre los muros: cada material cubre lo mismo,
+            // aunque el ruido se junte cerca de 0.5
+            let scale = scale.max(1.0);
+            let noise = move |(i, j): Cell| value_noise(seed, i as f32 / scale, j as f32 / scale);
+            let mut sorted: Vec<f32> = maze.cells().filter(|&(_, t)| t.is_material()).map(|(c, _)| noise(c)).collect();
+            sorted.sort_by(f32::total_cmp);
+            let total = sorted.len().max(1) as f32;
+            Box::new(move |c: Cell| {
+                let v = noise(c);
+                Some(pick(sorted.partition_point(|&x| x < v) as f32 / total))
+            })
+        }
+        Painter::Voronoi { zones } => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let first = rng.gen_range(0..n); // los materiales se turnan entre zonas
+            let sites: Vec<(f32, f32, Tile)> = (0..zones.max(1))
+                .map(|k| (rng.gen_range(0.0..w as f32), rng.gen_range(0.0..h as f32), paint.palette[(first + k) % n]))
+                .collect();
+            Box::new(move |(i, j): Cell| {
+                let d2 = |&(x, y, _): &(f32, f32, Tile)| (x - i as f32).powi(2) + (y - j as f32).powi(2);
+                sites.iter().min_by(|a, b| d2(a).total_cmp(&d2(b))).map(|s| s.2)
+            })
+        }
+        Painter::Depth => {
+            let depth = depth_map(maze);
+            let storeys = storeys.max(1) as f32;
+            Box::new(move |(i, j): Cell| {
+                let t = (storey as f32 + depth[j * w + i]) / storeys;
+                // un poco de ruido para que los cambios no queden en línea recta
+                let jitter = (hash(seed, i as i64, j as i64) - 0.5) / n as f32;
+                Some(pick((t + jitter).clamp(0.0, 1.0)))
+            })
+        }
+        Painter::Rooms => {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let themes: Vec<Tile> = rooms.iter()
+                .map(|_| if n > 1 { paint.palette[rng.gen_range(1..n)] } else { paint.palette[0] })
+                .collect();
+            Box::new(move |(i, j): Cell| {
+                // muro de una sala = a un tile o menos de su interior (diagonales incluidas)
+                let near = |r: &Room| i + 1 >= r.x && i <= r.x + r.w && j + 1 >= r.y && j <= r.y + r.h;
+                Some(rooms.iter().position(near).map_or(paint.palette[0], |k| themes[k]))
+            })
+        }
+    };
+
+    for j in 0..h {
+        for i in 0..w {
+            if !maze[(i, j)].is_material() { continue; }
+            if let Some(t) = material((i, j)) { maze[(i, j)] = t; }
+        }
+    }
+}
+
+/// Profundidad 0..1 de cada tile: distancia a pie desde la entrada del piso
+/// ('p', o si no hay, su primera escalera o ascensor) relativa a la más
+/// lejana. Un muro toma la de su vecino transitable más cercano a la entrada;
+/// los que no tocan ninguno, la máxima.
+fn depth_map(maze: &Maze) -> Vec<f32> {
+    let (w, h) = (maze.width(), maze.height());
+    let entry = maze.find(Tile::Start)
+        .or_else(|| maze.cells().find(|&(_, t)| t.is_storey_link()).map(|(c, _)| c));
+    let Some(entry) = entry else { return vec![0.0; w * h] };
+    let field = distance_field(maze, entry);
+    let max = field.farthest().map_or(1, |(_, d)| d.max(1)) as f32;
+
+    let mut out = vec![1.0; w * h];
+    for ((i, j), t) in maze.cells() {
+        let d = if t.is_walkable() {
+            field.get((i, j))
+        } else {
+            (-1isize..=1).flat_map(|dy| (-1isize..=1).map(move |dx| (dx, dy)))
+                .filter_map(|(dx, dy)| field.get((i.checked_add_signed(dx)?, j.checked_add_signed(dy)?)))
+                .min()
+        };
+        if let Some(d) = d { out[j * w + i] = d as f32 / max; }
+    }
+    out
+}
+
+/// Ruido de valor suave en 0..1: valores al azar en los puntos enteros e
+/// interpolación bilineal con suavizado entre ellos.
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let at = |dx: i64, dy: i64| hash(seed, x0 as i64 + dx, y0 as i64 + dy);
+    let top = at(0, 0) + (at(1, 0) - at(0, 0)) * tx;
+    let bottom = at(0, 1) + (at(1, 1) - at(0, 1)) * tx;
+    top + (bottom - top) * ty
+}
+
+/// Número pseudoaleatorio en 0..1 para un punto de la red (splitmix64).
+fn hash(seed: u64, x: i64, y: i64) -> f32 {
+    let mut z = seed
+        .wrapping_add((x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+        .wrapping_add((y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
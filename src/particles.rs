@@ -0,0 +1,233 @@
+// src/particles.rs
+use rand::{thread_rng, Rng};
+use rand::rngs::ThreadRng;
+use raylib::color::Color;
+use crate::{framebuffer::Framebuffer, player::Player, renderer3d::Atmosphere};
+
+/// Parámetros de emisión (todo en unidades de mundo y segundos).
+#[derive(Clone, Copy)]
+pub struct EmitterParams {
+    pub rate: f32,            // partículas por segundo
+    pub life: (f32, f32),     // vida (min, max)
+    pub speed: (f32, f32),    // velocidad horizontal (min, max)
+    pub up: (f32, f32),       // velocidad vertical inicial (min, max)
+    pub spread: f32,          // radio de aparición alrededor del emisor
+    pub gravity: f32,         // aceleración vertical (negativa = cae)
+    pub size: (f32, f32),     // tamaño al nacer y al morir
+    pub color: (Color, Color),// color al nacer y al morir (alpha incluido)
+}
+
+/// Polvo levantado al caminar.
+pub fn dust() -> EmitterParams {
+    EmitterParams {
+        rate: 18.0,
+        life: (0.4, 0.8),
+        speed: (4.0, 14.0),
+        up: (6.0, 16.0),
+        spread: 6.0,
+        gravity: -30.0,
+        size: (2.0, 5.0),
+        color: (Color::new(150, 130, 100, 160), Color::new(120, 105, 80, 0)),
+    }
+}
+
+/// Destellos alrededor de la meta.
+pub fn sparkles() -> EmitterParams {
+    EmitterParams {
+        rate: 25.0,
+        life: (0.6, 1.4),
+        speed: (2.0, 10.0),
+        up: (10.0, 30.0),
+        spread: 18.0,
+        gravity: -8.0,
+        size: (2.5, 0.5),
+        color: (Color::new(255, 255, 160, 255), Color::new(255, 200, 40, 0)),
+    }
+}
+
+/// Humo que sube (antorchas, respiraderos).
+pub fn smoke() -> EmitterParams {
+    EmitterParams {
+        rate: 10.0,
+        life: (1.5, 2.5),
+        speed: (1.0, 5.0),
+        up: (12.0, 20.0),
+        spread: 3.0,
+        gravity: 4.0,
+        size: (3.0, 12.0),
+        color: (Color::new(90, 90, 90, 140), Color::new(60, 60, 60, 0)),
+    }
+}
+
+/// Nube corta al chocar contra una pared (se usa con `burst`).
+pub fn impact() -> EmitterParams {
+    EmitterParams {
+        rate: 0.0,
+        life: (0.2, 0.45),
+        speed: (10.0, 35.0),
+        up: (-10.0, 20.0),
+        spread: 2.0,
+        gravity: -60.0,
+        size: (3.0, 1.0),
+        color: (Color::new(200, 190, 170, 200), Color::new(160, 150, 130, 0)),
+    }
+}
+
+struct Particle {
+    x: f32, y: f32, z: f32,
+    vx: f32, vy: f32, vz: f32,
+    age: f32,
+    life: f32,
+    gravity: f32,
+    size: (f32, f32),
+    color: (Color, Color),
+}
+
+pub struct Emitter {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub params: EmitterParams,
+    pub active: bool,
+    acc: f32, // fracción de partícula acumulada entre frames
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    pub emitters: Vec<Emitter>,
+    max: usize,
+    rng: ThreadRng,
+}
+
+impl ParticleSystem {
+    pub fn new(max: usize) -> Self {
+        Self { particles: Vec::with_capacity(max), emitters: Vec::new(), max, rng: thread_rng() }
+    }
+
+    /// Elimina partículas y emisores (al cambiar de nivel).
+    pub fn clear(&mut self) {
+        self.particles.clear();
+        self.emitters.clear();
+    }
+
+    /// Agrega un emisor continuo y devuelve su índice.
+    pub fn add_emitter(&mut self, x: f32, y: f32, z: f32, params: EmitterParams) -> usize {
+        self.emitters.push(Emitter { x, y, z, params, active: true, acc: 0.0 });
+        self.emitters.len() - 1
+    }
+
+    /// Emite `count` partículas de golpe en (x, y, z).
+    pub fn burst(&mut self, x: f32, y: f32, z: f32, count: usize, params: &EmitterParams) {
+        for _ in 0..count {
+            self.spawn(x, y, z, params);
+        }
+    }
+
+    fn spawn(&mut self, x: f32, y: f32, z: f32, p: &EmitterParams) {
+        if self.particles.len() >= self.max { return; }
+        let rng = &mut self.rng;
+        let ang = rng.gen_range(0.0..std::f32::consts::TAU);
+        let off = rng.gen_range(0.0..=p.spread);
+        let spd = rng.gen_range(p.speed.0..=p.speed.1);
+        self.particles.push(Particle {
+            x: x + ang.cos() * off,
+            y: y + ang.sin() * off,
+            z,
+            vx: ang.cos() * spd,
+            vy: ang.sin() * spd,
+            vz: rng.gen_range(p.up.0..=p.up.1),
+            age: 0.0,
+            life: rng.gen_range(p.life.0..=p.life.1),
+            gravity: p.gravity,
+            size: p.size,
+            color: p.color,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        // Emisores continuos
+        for e in 0..self.emitters.len() {
+            let (x, y, z, params) = {
+                let em = &mut self.emitters[e];
+                if !em.active { em.acc = 0.0; continue; }
+                em.acc += em.params.rate * dt;
+                (em.x, em.y, em.z, em.params)
+            };
+            while self.emitters[e].acc >= 1.0 {
+                self.emitters[e].acc -= 1.0;
+                self.spawn(x, y, z, &params);
+            }
+        }
+
+        // Integración + muerte (el suelo frena las partículas)
+        for p in self.particles.iter_mut() {
+            p.age += dt;
+            p.vz  += p.gravity * dt;
+            p.x   += p.vx * dt;
+            p.y   += p.vy * dt;
+            p.z   += p.vz * dt;
+            if p.z < 0.0 { p.z = 0.0; p.vz = 0.0; p.vx *= 0.5; p.vy *= 0.5; }
+        }
+        self.particles.retain(|p| p.age < p.life);
+    }
+
+    /// Dibuja todas las partículas como cuadrados de color plano, con el mismo
+    /// test de profundidad que los sprites (`zbuf` por columna, `sbuf` por píxel).
+    /// Primero se proyectan y ordenan todas, luego se rasterizan en un solo pase.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        fb: &mut Framebuffer,
+        player: &Player,
+        zbuf: &[f32],
+        sbuf: &[f32],
+        fov: f32,
+        atmo: &Atmosphere,
+        eye_z: f32,
+    ) {
+        let hw = fb.width  as f32 / 2.0;
+        let hh = fb.height as f32 / 2.0;
+        let proj = hw / (fov / 2.0).tan();
+        let (ca, sa) = (player.a.cos(), player.a.sin());
+
+        // (profundidad, x0, y0, lado, color)
+        let mut quads: Vec<(f32, i32, i32, i32, Color)> = Vec::with_capacity(self.particles.len());
+        for p in &self.particles {
+            let dx = p.x - player.pos.x;
+            let dy = p.y - player.pos.y;
+            let depth = dx * ca + dy * sa;
+            if depth <= 1.0 { continue; }
+            let side = dx * -sa + dy * ca; // desplazamiento lateral (derecha +)
+
+            let t = (p.age / p.life).clamp(0.0, 1.0);
+            let size = p.size.0 + (p.size.1 - p.size.0) * t;
+            let px = ((size * proj / depth) as i32).max(1);
+
+            // Misma proyección angular que paredes y sprites
+            let sx = hw + (side.atan2(depth) / (fov / 2.0)) * hw;
+            let sy = hh + (eye_z - p.z) * proj / depth;
+
+            let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t) as u8;
+            let (c0, c1) = p.color;
+            let col = atmo.fog_color(
+                Color::new(lerp(c0.r, c1.r), lerp(c0.g, c1.g), lerp(c0.b, c1.b), lerp(c0.a, c1.a)),
+                dx.hypot(dy),
+            );
+            if col.a == 0 { continue; }
+
+            quads.push((depth, sx as i32 - px / 2, sy as i32 - px / 2, px, col));
+        }
+        quads.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let (w, h) = (fb.width as i32, fb.height as i32);
+        for &(depth, x0, y0, side, col) in &quads {
+            for xs in x0.max(0)..(x0 + side).min(w) {
+                if depth >= zbuf.get(xs as usize).copied().unwrap_or(f32::INFINITY) { continue; }
+                for ys in y0.max(0)..(y0 + side).min(h) {
+                    if depth >= sbuf[(ys * w + xs) as usize] { continue; }
+                    fb.blend_pixel(xs as u32, ys as u32, col);
+                }
+            }
+        }
+    }
+}
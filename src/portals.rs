@@ -0,0 +1,126 @@
+// src/portals.rs
+use std::collections::HashMap;
+use rand::RngCore;
+use rand::seq::SliceRandom;
+use crate::maze::Maze;
+use crate::tile::Tile;
+
+// Celda portal: `Tile::Portal(1..=9)` ('1'..'9' en texto). Cada número aparece
+// dos veces y las dos celdas quedan enlazadas. La "puerta" de un portal es su
+// único vecino abierto; cruzarla hacia dentro te saca por la puerta del otro extremo.
+
+pub struct PortalLink {
+    pub to: (usize, usize),
+    pub axis_in: (f32, f32), // dirección de entrada (hacia dentro del portal)
+    pub rot: f32,            // giro aplicado a posición y ángulo al cruzar
+}
+
+/// Enlaces entre portales + profundidad máxima de recursión de rayos
+/// (rebotes en espejos 'M' y saltos por portales, sumados).
+pub struct Portals {
+    links: HashMap<(usize, usize), PortalLink>,
+    pub max_depth: u32,
+}
+
+const DIRS: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+fn door_of(maze: &Maze, i: usize, j: usize) -> Option<(isize, isize)> {
+    DIRS.iter().copied().find(|&(dx, dy)| {
+        let t = maze.get_signed(i as isize + dx, j as isize + dy);
+        matches!(t, Some(Tile::Floor | Tile::Start | Tile::Goal))
+    })
+}
+
+impl Portals {
+    /// Busca los pares de dígitos del laberinto y los enlaza. Un dígito sin
+    /// pareja o sin puerta queda como celda normal (no transporta).
+    pub fn from_maze(maze: &Maze, max_depth: u32) -> Self {
+        let mut by_digit: HashMap<u8, Vec<(usize, usize)>> = HashMap::new();
+        for (c, t) in maze.cells() {
+            if let Tile::Portal(n) = t { by_digit.entry(n).or_default().push(c); }
+        }
+
+        let mut links = HashMap::new();
+        for cells in by_digit.values() {
+            let &[a, b] = cells.as_slice() else { continue };
+            let (Some(da), Some(db)) = (door_of(maze, a.0, a.1), door_of(maze, b.0, b.1)) else { continue };
+            for (from, to, dfrom, dto) in [(a, b, da, db), (b, a, db, da)] {
+                let axis_in = (-dfrom.0 as f32, -dfrom.1 as f32);
+                let rot = (dto.1 as f32).atan2(dto.0 as f32) - axis_in.1.atan2(axis_in.0);
+                links.insert(from, PortalLink { to, axis_in, rot });
+            }
+        }
+        Self { links, max_depth }
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Option<&PortalLink> {
+        self.links.get(&(i, j))
+    }
+
+    /// Lleva un punto dentro del portal `from` al punto equivalente del otro
+    /// extremo: se refleja el eje de entrada y se gira por `rot`, de modo que
+    /// entrar por la puerta de uno es salir por la puerta del otro.
+    pub fn transform(link: &PortalLink, from: (usize, usize), x: f32, y: f32, block_size: usize) -> (f32, f32) {
+        let bs = block_size as f32;
+        let (ax, ay) = ((from.0 as f32 + 0.5) * bs, (from.1 as f32 + 0.5) * bs);
+        let (bx, by) = ((link.to.0 as f32 + 0.5) * bs, (link.to.1 as f32 + 0.5) * bs);
+
+        let (mut ox, mut oy) = (x - ax, y - ay);
+        let along = ox * link.axis_in.0 + oy * link.axis_in.1;
+        ox -= 2.0 * along * link.axis_in.0;
+        oy -= 2.0 * along * link.axis_in.1;
+
+        let (s, c) = link.rot.sin_cos();
+        (bx + ox * c - oy * s, by + ox * s + oy * c)
+    }
+}
+
+fn dead_ends(maze: &Maze) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    for ((i, j), t) in maze.cells() {
+        if t != Tile::Floor { continue; }
+        let open = DIRS.iter().filter(|&&(dx, dy)| {
+            maze.get_signed(i as isize + dx, j as isize + dy).is_some_and(Tile::is_walkable)
+        }).count();
+        if open == 1 { out.push((i, j)); }
+    }
+    out
+}
+
+/// Convierte hasta `pairs` pares de callejones sin salida en portales enlazados,
+/// emparejando los más alejados entre sí.
+pub fn place_portal_pairs(maze: &mut Maze, pairs: usize) {
+    let mut ends = dead_ends(maze);
+    for digit in (1..=9).take(pairs) {
+        if ends.len() < 2 { break; }
+        let mut best = (0, 1, 0);
+        for a in 0..ends.len() {
+            for b in a + 1..ends.len() {
+                let d = ends[a].0.abs_diff(ends[b].0) + ends[a].1.abs_diff(ends[b].1);
+                if d > best.2 { best = (a, b, d); }
+            }
+        }
+        let (b, a) = (ends.remove(best.1), ends.remove(best.0));
+        maze[a] = Tile::Portal(digit);
+        maze[b] = Tile::Portal(digit);
+    }
+}
+
+/// Convierte `count` muros interiores que dan a un pasillo en espejos.
+pub fn place_mirrors(maze: &mut Maze, count: usize, rng: &mut dyn RngCore) {
+    let (w, h) = (maze.width(), maze.height());
+    let mut candidates = Vec::new();
+    for j in 1..h - 1 {
+        for i in 1..w - 1 {
+            if !maze[(i, j)].is_material() { continue; }
+            let faces_floor = DIRS.iter().any(|&(dx, dy)| {
+                maze[((i as isize + dx) as usize, (j as isize + dy) as usize)] == Tile::Floor
+            });
+            if faces_floor { candidates.push((i, j)); }
+        }
+    }
+    candidates.shuffle(rng);
+    for &(i, j) in candidates.iter().take(count) {
+        maze[(i, j)] = Tile::Mirror;
+    }
+}
@@ -0,0 +1,92 @@
+// src/records.rs
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Mejores tiempos por nivel (clave = `LevelDef::id`), guardados como
+/// líneas `id<TAB>segundos` en un archivo de texto.
+pub struct BestTimes {
+    path: PathBuf,
+    times: HashMap<String, f32>,
+}
+
+impl BestTimes {
+    /// Lee el archivo si existe; si no, empieza vacío. Las líneas que no se
+    /// entienden se ignoran (no vale la pena perder los demás récords).
+    pub fn load(path: PathBuf) -> Self {
+        let times = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|l| {
+                let (id, secs) = l.split_once('\t')?;
+                Some((id.to_string(), secs.trim().parse().ok()?))
+            })
+            .collect();
+        Self { path, times }
+    }
+
+    pub fn get(&self, id: &str) -> Option<f32> {
+        self.times.get(id).copied()
+    }
+
+    /// Registra un tiempo; devuelve true si es récord (y lo guarda a disco).
+    pub fn submit(&mut self, id: &str, seconds: f32) -> bool {
+        if self.get(id).is_some_and(|best| best <= seconds) {
+            return false;
+        }
+        self.times.insert(id.to_string(), seconds);
+        if let Err(e) = self.save() {
+            eprintln!("No se pudieron guardar los récords en {}: {e}", self.path.display());
+        }
+        true
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let mut ids: Vec<&String> = self.times.keys().collect();
+        ids.sort();
+        let text: String = ids.iter().map(|id| format!("{id}\t{:.3}\n", self.times[*id])).collect();
+        fs::write(&self.path, text)
+    }
+}
+
+/// Carreras del modo sin fin, una por línea (`semilla<TAB>profundidad<TAB>segundos`),
+/// para mostrar la mejor en el menú.
+pub struct RunLog {
+    path: PathBuf,
+    best: Option<(usize, f32)>,
+}
+
+impl RunLog {
+    pub fn load(path: PathBuf) -> Self {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let best = text.lines()
+            .filter_map(|l| {
+                let mut parts = l.split('\t').skip(1);
+                Some((parts.next()?.trim().parse().ok()?, parts.next()?.trim().parse().ok()?))
+            })
+            .fold(None, |best, run| if better(run, best) { Some(run) } else { best });
+        Self { path, best }
+    }
+
+    /// Mejor carrera: la más profunda y, a igual profundidad, la más rápida.
+    pub fn best(&self) -> Option<(usize, f32)> {
+        self.best
+    }
+
+    /// Agrega una carrera al archivo; devuelve true si es la mejor.
+    pub fn submit(&mut self, seed: u64, depth: usize, seconds: f32) -> bool {
+        let line = format!("{seed}\t{depth}\t{seconds:.3}\n");
+        let saved = fs::OpenOptions::new().create(true).append(true).open(&self.path)
+            .and_then(|mut f| std::io::Write::write_all(&mut f, line.as_bytes()));
+        if let Err(e) = saved {
+            eprintln!("No se pudo guardar la carrera en {}: {e}", self.path.display());
+        }
+        let record = better((depth, seconds), self.best);
+        if record { self.best = Some((depth, seconds)); }
+        record
+    }
+}
+
+fn better((depth, secs): (usize, f32), best: Option<(usize, f32)>) -> bool {
+    best.is_none_or(|(d, s)| depth > d || (depth == d && secs < s))
+}
@@ -4,20 +4,113 @@ use crate::{
     framebuffer::Framebuffer,
     maze::Maze,
     player::Player,
-    caster::cast_ray,
+    caster::{cast_ray, hit_face, RaySegment},
+    decals::{composite, DecalMap},
+    portals::Portals,
     texture::Texture,
     textures::TextureAtlas,
 };
 
-/// Render de paredes + escritura de z-buffer (distancia perpendicular por columna).
+/// Atenuación por cada rebote en espejo o salto de portal.
+pub const MIRROR_DIM: f32 = 0.8;
+
+/// Distancia a partir de la cual todo queda en negro (ancho del mapa en px).
+pub fn max_view_distance(maze: &Maze, block_size: usize) -> f32 {
+    (maze.width() * block_size) as f32
+}
+
+/// Luz puntual en coordenadas de mundo; ilumina paredes y sprites cercanos.
+#[derive(Clone, Copy)]
+pub struct Light {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// Cielo, suelo, niebla e iluminación de un nivel.
+#[derive(Clone)]
+pub struct Atmosphere {
+    pub sky: Color,
+    pub floor: Color,
+    pub fog: Color,
+    pub fog_distance: f32, // a esta distancia todo es niebla
+    pub ambient: f32,      // luz base (1.0 = textura tal cual)
+    pub lights: Vec<Light>,
+}
+
+impl Atmosphere {
+    /// El aspecto original: cielo azul, suelo negro y fundido a negro.
+    pub fn classic(fog_distance: f32) -> Self {
+        Self {
+            sky: Color::SKYBLUE,
+            floor: Color::BLACK,
+            fog: Color::BLACK,
+            fog_distance,
+            ambient: 1.0,
+            lights: Vec::new(),
+        }
+    }
+
+    /// Multiplicador de luz (r, g, b) en un punto del mapa.
+    pub fn light_at(&self, x: f32, y: f32) -> [f32; 3] {
+        let mut out = [self.ambient; 3];
+        for l in &self.lights {
+            let d = (x - l.x).hypot(y - l.y);
+            if d >= l.radius { continue; }
+            let k = (1.0 - d / l.radius).powi(2);
+            out[0] += k * l.color.r as f32 / 255.0;
+            out[1] += k * l.color.g as f32 / 255.0;
+            out[2] += k * l.color.b as f32 / 255.0;
+        }
+        out
+    }
 
+    #[inline]
+    fn fog_factor(&self, distance: f32) -> f32 {
+        (distance / self.fog_distance).min(1.0)
+    }
+
+    /// Ilumina un texel 0xAARRGGBB, lo oscurece por `dim` y lo funde con la niebla.
+    #[inline]
+    pub fn shade(&self, texel: u32, distance: f32, light: [f32; 3], dim: f32) -> Color {
+        let f = self.fog_factor(distance);
+        let ch = |shift: u32, l: f32, fog: u8| {
+            let c = ((texel >> shift) & 0xFF) as f32 * l * dim;
+            (c * (1.0 - f) + fog as f32 * f).min(255.0) as u8
+        };
+        Color::new(
+            ch(16, light[0], self.fog.r),
+            ch(8,  light[1], self.fog.g),
+            ch(0,  light[2], self.fog.b),
+            ((texel >> 24) & 0xFF) as u8,
+        )
+    }
+
+    /// Funde un color plano con la niebla (conserva su alpha).
+    pub fn fog_color(&self, c: Color, distance: f32) -> Color {
+        let f = self.fog_factor(distance);
+        let mix = |a: u8, b: u8| (a as f32 * (1.0 - f) + b as f32 * f) as u8;
+        Color::new(mix(c.r, self.fog.r), mix(c.g, self.fog.g), mix(c.b, self.fog.b), c.a)
+    }
+}
+
+/// Render de paredes + escritura de z-buffer (distancia perpendicular por columna).
+/// Las calcomanías de `decals` se mezclan sobre la textura de cada cara;
+/// cielo, suelo, luces y niebla salen de `atmo`.
+/// En `seen` quedan, por columna, los tramos del rayo tras espejos/portales.
+#[allow(clippy::too_many_arguments)]
 pub fn render3d(
     framebuffer: &mut Framebuffer,
     maze: &Maze,
     player: &Player,
     block_size: usize,
     atlas: &TextureAtlas,
-    zbuf: &mut [f32],         
+    decals: &DecalMap,
+    portals: &Portals,
+    atmo: &Atmosphere,
+    zbuf: &mut [f32],
+    seen: &mut [Vec<RaySegment>],
 ) {
     let num_rays        = framebuffer.width;
     let hw              = framebuffer.width  as f32 / 2.0;
@@ -25,35 +118,37 @@ pub fn render3d(
     let dist_proj_plane = hw / (player.fov / 2.0).tan();
 
     // Cielo
-    framebuffer.set_current_color(Color::SKYBLUE);
+    framebuffer.set_current_color(atmo.sky);
     for y in 0..(hh as u32) {
         for x in 0..framebuffer.width {
             framebuffer.set_pixel(x, y);
         }
     }
     // Suelo
-    framebuffer.set_current_color(Color::BLACK);
+    framebuffer.set_current_color(atmo.floor);
     for y in (hh as u32)..framebuffer.height {
         for x in 0..framebuffer.width {
             framebuffer.set_pixel(x, y);
         }
     }
 
-    let max_distance = (maze[0].len() * block_size) as f32;
     let step = 2;
 
     for col in (0..num_rays).step_by(step as usize) {
         let current_ray = col as f32 / num_rays as f32;
         let angle = player.a - (player.fov / 2.0) + (player.fov * current_ray);
 
-        let hit = cast_ray(framebuffer, maze, player, angle, block_size, false);
+        let hit = cast_ray(framebuffer, maze, player, angle, block_size, portals, false);
         let distance  = hit.distance.max(0.0001);
         let dist_perp = distance * (angle - player.a).cos(); 
 
         // >>> Escribir z-buffer para estas columnas 
+        // (hasta la primera superficie: lo visto en espejos/portales no ocluye sprites)
+        let first_perp = hit.first_distance.max(0.0001) * (angle - player.a).cos();
         for dx in 0..step {
             let x = (col + dx as u32).min(num_rays - 1) as usize;
-            zbuf[x] = dist_perp;
+            zbuf[x] = first_perp;
+            seen[x].clone_from(&hit.segments);
         }
 
         // Altura de la columna
@@ -62,35 +157,20 @@ pub fn render3d(
         let bottom = (hh + stake_height / 2.0).min(framebuffer.height as f32) as i32;
 
         // Punto de impacto en mundo
-        let hit_x = player.pos.x + distance * angle.cos();
-        let hit_y = player.pos.y + distance * angle.sin();
-
-        // Coordenadas dentro del bloque
-        let lx = ((hit_x as i32 % block_size as i32) + block_size as i32) % block_size as i32;
-        let ly = ((hit_y as i32 % block_size as i32) + block_size as i32) % block_size as i32;
-
-        // Detectar lado -> coord U
-        let to_left   = lx;
-        let to_right  = (block_size as i32 - 1) - lx;
-        let to_top    = ly;
-        let to_bottom = (block_size as i32 - 1) - ly;
-
-        let (_, mut u) = {
-            let min_vert = to_left.min(to_right);
-            let min_hori = to_top.min(to_bottom);
-            if min_vert <= min_hori {
-                (true,  ly as f32 / (block_size.saturating_sub(1)) as f32)
-            } else {
-                (false, lx as f32 / (block_size.saturating_sub(1)) as f32)
-            }
-        };
-        if u < 0.0 { u += 1.0; }
+        let (hit_x, hit_y) = (hit.hit_x, hit.hit_y);
+
+        // Cara golpeada -> coord U
+        let (face, u) = hit_face(hit_x, hit_y, block_size);
+        let cell = (hit_x.max(0.0) as usize / block_size, hit_y.max(0.0) as usize / block_size);
+        let wall_decals = decals.get(cell.0, cell.1, face);
 
-        // Textura según el char del muro impactado
+        // Textura según el tile del muro impactado
         let wall_tex: &Texture = atlas.get(hit.impact);
 
-        // Sombreado por distancia
-        let shade = 1.0 - (distance / max_distance).min(1.0);
+        // Luz en el punto de impacto; niebla por distancia
+        // (cada rebote/portal oscurece un poco, para que se note el reflejo)
+        let light = atmo.light_at(hit_x, hit_y);
+        let dim = MIRROR_DIM.powi(hit.bounces as i32);
 
         // Dibujar columna texturizada
         let y0 = top.max(0);
@@ -102,15 +182,12 @@ pub fn render3d(
 
             for y in y0..=y1 {
                 let v = (y - y0) as f32 / denom; // 0..1 vertical
-                let texel = wall_tex.sample(u, v);
-
-                let a = ((texel >> 24) & 0xFF) as u8;
-                let mut r = ((texel >> 16) & 0xFF) as f32;
-                let mut g = ((texel >> 8)  & 0xFF) as f32;
-                let mut b = ( texel        & 0xFF) as f32;
-                r *= shade; g *= shade; b *= shade;
+                let mut texel = wall_tex.sample(u, v);
+                if !wall_decals.is_empty() {
+                    texel = composite(wall_decals, face.local_u(u), v, texel);
+                }
 
-                framebuffer.set_current_color(Color::new(r as u8, g as u8, b as u8, a));
+                framebuffer.set_current_color(atmo.shade(texel, distance, light, dim));
                 framebuffer.set_pixel(x_screen as u32, y as u32);
             }
         }
@@ -0,0 +1,449 @@
+// src/sharecode.rs
+use std::fmt;
+use std::path::PathBuf;
+use crate::bytes::{ByteReader, ByteWriter, ReadError};
+use crate::dungeon::DungeonOptions;
+use crate::generators::{Algorithm, TreeBias};
+use crate::level::{Generator, LevelDef, LevelSource, ProcSpec, SpriteSpec};
+use crate::maze::Placement;
+use crate::painters::{Painter, WallPaint};
+use crate::sprites::SpriteAnchor;
+use crate::storeys::Link;
+use crate::tile::Tile;
+
+/// Versión del formato: sube si cambia el orden o el sentido de los campos.
+const VERSION: u8 = 1;
+
+/// Base32 de Crockford: sin I, L, O ni U, que se confunden al copiarlo a mano.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Algoritmos sin parámetros, por su número en el código (7 = growing-tree, 8 = salas).
+const ALGORITHMS: [Algorithm; 7] = [
+    Algorithm::Backtracker, Algorithm::Prim, Algorithm::Kruskal, Algorithm::Wilson,
+    Algorithm::Eller, Algorithm::BinaryTree, Algorithm::Division,
+];
+const BIASES: [TreeBias; 4] = [TreeBias::Newest, TreeBias::Oldest, TreeBias::Random, TreeBias::Middle];
+const ANCHORS: [SpriteAnchor; 3] = [SpriteAnchor::Floor, SpriteAnchor::Center, SpriteAnchor::Ceiling];
+
+/// Límites al leer, para que un código roto no pida un mapa gigante.
+const MAX_CELLS: usize = 256;
+const MAX_STOREYS: usize = 16;
+
+#[derive(Debug)]
+pub enum CodeError {
+    Char(char),
+    Checksum,
+    Version(u8),
+    Invalid(&'static str),
+}
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodeError::Char(c)    => write!(f, "carácter inválido '{c}'"),
+            CodeError::Checksum   => write!(f, "el código está incompleto o mal copiado"),
+            CodeError::Version(v) => write!(f, "código de otra versión del juego ({v})"),
+            CodeError::Invalid(m) => write!(f, "código inválido: {m}"),
+        }
+    }
+}
+
+impl From<ReadError> for CodeError {
+    fn from(e: ReadError) -> Self {
+        CodeError::Invalid(e.0)
+    }
+}
+
+/// Código para compartir un nivel procedural con la semilla con la que se
+/// jugó; los niveles hechos a mano no tienen. Lleva lo que decide el mapa,
+/// las cajas y los obstáculos: generador y parámetros, semilla y los sprites
+/// del archivo (no la luz, la niebla ni las texturas).
+///
+/// Bytes: versión, campos (enteros en varint LEB128, decimales como `f32`
+/// exactos) y 2 de suma de control; en texto, base32 de Crockford en grupos
+/// de 5 separados por '-' (p. ej. `0G6X1-2DQ0Y-...`).
+///
+/// Un nivel fuera de los límites de lectura (`MAX_CELLS`, `MAX_STOREYS`...)
+/// no tiene código: se comprueba leyéndolo de vuelta, así que todo código
+/// que sale de aquí se lee y rehace el mismo nivel.
+pub fn level_code(def: &LevelDef, seed: u64) -> Option<String> {
+    let LevelSource::Procedural(p) = &def.source else { return None };
+    let bytes = encode(def, p, seed);
+    let mut full = bytes.clone();
+    full.extend(checksum(&bytes).to_be_bytes());
+    let text = to_text(&full);
+
+    let back = level_from_code(&text, def.dir.clone()).ok()?;
+    let LevelSource::Procedural(bp) = &back.source else { return None };
+    (encode(&back, bp, seed) == bytes).then_some(text)
+}
+
+/// Los bytes del código, sin la suma de control.
+fn encode(def: &LevelDef, p: &ProcSpec, seed: u64) -> Vec<u8> {
+    let mut w = ByteWriter(vec![VERSION]);
+    write_generator(&mut w, p.generator);
+    w.var(p.cells.0 as u64);
+    w.var(p.cells.1 as u64);
+    w.var(seed);
+    w.byte(p.braid);
+    w.var(p.mirrors as u64);
+    w.var(p.portal_pairs as u64);
+    match p.placement {
+        Placement::Fixed             => w.byte(0),
+        Placement::FarthestFromStart => w.byte(1),
+        Placement::Diameter          => w.byte(2),
+        Placement::RandomMinPath(n)  => { w.byte(3); w.var(n as u64); }
+    }
+    w.var(p.storeys as u64);
+    w.byte((p.link == Link::Elevator) as u8);
+    match p.difficulty {
+        Some(d) => { w.byte(1); w.f32(d); }
+        None => w.byte(0),
+    }
+    write_walls(&mut w, &p.walls);
+    w.var(def.sprites.len() as u64);
+    for s in &def.sprites {
+        w.f32(s.x);
+        w.f32(s.y);
+        w.str(&s.texture);
+        w.f32(s.radius);
+        w.f32(s.size);
+        w.byte(ANCHORS.iter().position(|&a| a == s.anchor).unwrap_or(0) as u8);
+        w.f32(s.z);
+        w.var(s.storey as u64);
+    }
+    w.0
+}
+
+/// Nivel a partir de un código (ver `level_code`), con su semilla como la del
+/// archivo. Las rutas de los sprites se buscan en `dir`.
+pub fn level_from_code(code: &str, dir: PathBuf) -> Result<LevelDef, CodeError> {
+    let bytes = from_text(code)?;
+    let (body, sum) = bytes.split_at(bytes.len().saturating_sub(2));
+    if sum.len() != 2 || checksum(body) != u16::from_be_bytes([sum[0], sum[1]]) {
+        return Err(CodeError::Checksum);
+    }
+    let mut r = ByteReader::new(body);
+    let version = r.byte()?;
+    if version != VERSION { return Err(CodeError::Version(version)); }
+
+    let generator = read_generator(&mut r)?;
+    let cells = (read_size(&mut r)?, read_size(&mut r)?);
+    if cells == (1, 1) { return Err(CodeError::Invalid("tamaño del mapa")); }
+    let seed = r.var()?;
+    let braid = r.byte()?;
+    let mirrors = r.var()? as usize;
+    let portal_pairs = r.var()? as usize;
+    let placement = match r.byte()? {
+        0 => Placement::Fixed,
+        1 => Placement::FarthestFromStart,
+        2 => Placement::Diameter,
+        3 => {
+            let n = r.var()?;
+            if n > (MAX_CELLS * MAX_CELLS) as u64 { return Err(CodeError::Invalid("distancia mínima a la meta")); }
+            Placement::RandomMinPath(n as usize)
+        }
+        _ => return Err(CodeError::Invalid("ubicación de inicio y meta")),
+    };
+    let storeys = r.var()? as usize;
+    if storeys == 0 || storeys > MAX_STOREYS { return Err(CodeError::Invalid("cantidad de pisos")); }
+    let link = match r.byte()? {
+        0 => Link::Stairs,
+        1 => Link::Elevator,
+        _ => return Err(CodeError::Invalid("unión entre pisos")),
+    };
+    let difficulty = match r.byte()? {
+        0 => None,
+        1 => {
+            let d = r.f32()?;
+            if !(d > 0.0 && d < 100.0) { return Err(CodeError::Invalid("dificultad")); }
+            Some(d)
+        }
+        _ => return Err(CodeError::Invalid("dificultad")),
+    };
+    let walls = read_walls(&mut r)?;
+    if walls.painter == Painter::Rooms && !matches!(generator, Generator::Rooms(_)) {
+        return Err(CodeError::Invalid("muros por sala sin salas"));
+    }
+
+    let mut sprites = Vec::new();
+    for _ in 0..r.var()? {
+        sprites.push(SpriteSpec {
+            x: r.f32()?,
+            y: r.f32()?,
+            texture: r.str()?,
+            radius: r.f32()?,
+            size: r.f32()?,
+            anchor: *ANCHORS.get(r.byte()? as usize).ok_or(CodeError::Invalid("ancla de sprite"))?,
+            z: r.f32()?,
+            storey: r.var()? as usize,
+        });
+    }
+    if sprites.iter().any(|s| s.storey >= storeys) { return Err(CodeError::Invalid("sprite fuera de los pisos")); }
+    if !r.done() { return Err(CodeError::Invalid("sobran datos")); }
+
+    let text = to_text(&bytes);
+    let short = text.split('-').next().unwrap_or_default();
+    let mut def = LevelDef::new(&format!("Código {short}"), dir);
+    def.id = format!("codigo-{text}");
+    def.sprites = sprites;
+    def.source = LevelSource::Procedural(ProcSpec {
+        generator,
+        cells,
+        seed: Some(seed),
+        braid,
+        mirrors,
+        portal_pairs,
+        placement,
+        storeys,
+        link,
+        difficulty,
+        walls,
+    });
+    Ok(def)
+}
+
+/// FNV-1a de 32 bits plegado a 16.
+fn checksum(bytes: &[u8]) -> u16 {
+    let h = bytes.iter().fold(0x811C_9DC5u32, |h, &b| (h ^ b as u32).wrapping_mul(0x0100_0193));
+    (h ^ (h >> 16)) as u16
+}
+
+fn to_text(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    let (mut acc, mut bits, mut n) = (0u32, 0, 0);
+    let mut push = |v: u32| {
+        if n > 0 && n % 5 == 0 { out.push('-'); }
+        out.push(ALPHABET[v as usize & 31] as char);
+        n += 1;
+    };
+    for &b in bytes {
+        acc = (acc << 8) | b as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            push(acc >> bits);
+        }
+    }
+    if bits > 0 { push(acc << (5 - bits)); }
+    out
+}
+
+/// Acepta minúsculas, espacios y guiones, y lee I/L como 1 y O como 0.
+fn from_text(code: &str) -> Result<Vec<u8>, CodeError> {
+    let mut out = Vec::new();
+    let (mut acc, mut bits) = (0u32, 0);
+    for c in code.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+        let v = match c.to_ascii_uppercase() {
+            'I' | 'L' => 1,
+            'O' => 0,
+            u => ALPHABET.iter().position(|&a| a as char == u).ok_or(CodeError::Char(c))? as u32,
+        };
+        acc = (acc << 5) | v;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn write_generator(w: &mut ByteWriter, g: Generator) {
+    match g {
+        Generator::Maze(Algorithm::GrowingTree(bias)) => {
+            w.byte(7);
+            match bias {
+                TreeBias::Mixed(pct) => { w.byte(4); w.byte(pct); }
+                b => w.byte(BIASES.iter().position(|&x| x == b).unwrap_or(0) as u8),
+            }
+        }
+        Generator::Maze(algo) => w.byte(ALGORITHMS.iter().position(|&a| a == algo).unwrap_or(0) as u8),
+        Generator::Rooms(o) => {
+            w.byte(8);
+            w.var(o.max_rooms as u64);
+            w.var(o.min_size as u64);
+            w.var(o.max_size as u64);
+            w.byte(o.extra);
+        }
+    }
+}
+
+fn read_generator(r: &mut ByteReader) -> Result<Generator, CodeError> {
+    Ok(match r.byte()? {
+        7 => {
+            let bias = match r.byte()? {
+                4 => TreeBias::Mixed(r.byte()?),
+                b => *BIASES.get(b as usize).ok_or(CodeError::Invalid("variante de growing-tree"))?,
+            };
+            Generator::Maze(Algorithm::GrowingTree(bias))
+        }
+        8 => {
+            let o = DungeonOptions {
+                max_rooms: r.var()? as usize,
+                min_size: r.var()? as usize,
+                max_size: r.var()? as usize,
+                extra: r.byte()?,
+            };
+            if o.max_rooms > MAX_CELLS || o.max_size > MAX_CELLS { return Err(CodeError::Invalid("opciones de salas")); }
+            Generator::Rooms(o)
+        }
+        a => Generator::Maze(*ALGORITHMS.get(a as usize).ok_or(CodeError::Invalid("generador"))?),
+    })
+}
+
+/// Pintor, su parámetro y los materiales (0 = los del pintor).
+fn write_walls(w: &mut ByteWriter, walls: &WallPaint) {
+    match walls.painter {
+        Painter::Stripes           => w.byte(0),
+        Painter::Noise { scale }   => { w.byte(1); w.f32(scale); }
+        Painter::Voronoi { zones } => { w.byte(2); w.var(zones as u64); }
+        Painter::Depth             => w.byte(3),
+        Painter::Rooms             => w.byte(4),
+    }
+    if walls.palette == walls.painter.default_palette() {
+        w.var(0);
+    } else {
+        w.var(walls.palette.len() as u64);
+        for t in &walls.palette { w.byte(t.to_char() as u8); }
+    }
+}
+
+fn read_walls(r: &mut ByteReader) -> Result<WallPaint, CodeError> {
+    let painter = match r.byte()? {
+        0 => Painter::Stripes,
+        1 => Painter::Noise { scale: r.f32()? },
+        2 => Painter::Voronoi { zones: (r.var()? as usize).min(MAX_CELLS) },
+        3 => Painter::Depth,
+        4 => Painter::Rooms,
+        _ => return Err(CodeError::Invalid("pintor de muros")),
+    };
+    let n = r.var()? as usize;
+    let mut palette = Vec::new();
+    for _ in 0..n {
+        let tile = Tile::from_char(r.byte()? as char).filter(|t| t.is_material());
+        palette.push(tile.ok_or(CodeError::Invalid("material de muro"))?);
+    }
+    if palette.is_empty() { palette = painter.default_palette(); }
+    Ok(WallPaint { painter, palette })
+}
+
+/// Ancho o alto en celdas.
+fn read_size(r: &mut ByteReader) -> Result<usize, CodeError> {
+    let v = r.var()? as usize;
+    if v == 0 || v > MAX_CELLS { return Err(CodeError::Invalid("tamaño del mapa")); }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(placement: Placement) -> LevelDef {
+        let mut def = LevelDef::new("prueba", PathBuf::new());
+        def.sprites.push(SpriteSpec {
+            x: 3.5, y: 1.25, texture: "crate".into(), radius: 0.2, size: 0.6,
+            anchor: SpriteAnchor::Ceiling, z: 1.0, storey: 1,
+        });
+        def.source = LevelSource::Procedural(ProcSpec {
+            generator: Generator::Maze(Algorithm::GrowingTree(TreeBias::Mixed(30))),
+            cells: (12, 9),
+            seed: None,
+            braid: 40,
+            mirrors: 2,
+            portal_pairs: 1,
+            placement,
+            storeys: 2,
+            link: Link::Elevator,
+            difficulty: Some(42.5),
+            walls: WallPaint { painter: Painter::Voronoi { zones: 5 }, palette: vec![Tile::Moss, Tile::Sandstone] },
+        });
+        def
+    }
+
+    #[test]
+    fn round_trip() {
+        let code = level_code(&sample(Placement::RandomMinPath(7)), 123_456_789).unwrap();
+        let def = level_from_code(&code, PathBuf::new()).unwrap();
+        let LevelSource::Procedural(p) = &def.source else { panic!("nivel procedural") };
+        assert_eq!(p.seed, Some(123_456_789));
+        assert_eq!(level_code(&def, 123_456_789).as_deref(), Some(code.as_str()));
+        assert_eq!(def.sprites.len(), 1);
+    }
+
+    #[test]
+    fn any_changed_char_fails_the_checksum() {
+        let code = level_code(&sample(Placement::Diameter), 99).unwrap();
+        for (k, c) in code.char_indices().filter(|&(_, c)| c != '-') {
+            // cambia el bit alto del símbolo: siempre es dato, nunca relleno
+            let v = ALPHABET.iter().position(|&a| a as char == c).unwrap() ^ 16;
+            let mut bad = code.clone();
+            bad.replace_range(k..k + 1, &(ALPHABET[v] as char).to_string());
+            assert!(matches!(level_from_code(&bad, PathBuf::new()), Err(CodeError::Checksum)), "posición {k}");
+        }
+    }
+
+    #[test]
+    fn other_version_is_rejected() {
+        let code = level_code(&sample(Placement::Fixed), 5).unwrap();
+        let mut bytes = from_text(&code).unwrap();
+        bytes.truncate(bytes.len() - 2);
+        bytes[0] = VERSION + 1;
+        bytes.extend(checksum(&bytes).to_be_bytes());
+        assert!(matches!(level_from_code(&to_text(&bytes), PathBuf::new()), Err(CodeError::Version(v)) if v == VERSION + 1));
+    }
+
+    #[test]
+    fn confusable_letters_read_as_digits() {
+        assert_eq!(from_text("I-l o i").unwrap(), from_text("11 01").unwrap());
+        let code = level_code(&sample(Placement::Fixed), 0).unwrap();
+        let sloppy = code.replace('1', "l").replace('0', "o").to_lowercase();
+        assert_eq!(level_from_code(&sloppy, PathBuf::new()).unwrap().id, level_from_code(&code, PathBuf::new()).unwrap().id);
+    }
+
+    fn with(mut def: LevelDef, f: impl FnOnce(&mut ProcSpec)) -> LevelDef {
+        if let LevelSource::Procedural(p) = &mut def.source { f(p); }
+        def
+    }
+
+    #[test]
+    fn every_code_decodes() {
+        let generators = [
+            Generator::Maze(Algorithm::Backtracker), Generator::Maze(Algorithm::Division),
+            Generator::Maze(Algorithm::GrowingTree(TreeBias::Oldest)), Generator::Rooms(DungeonOptions::default()),
+        ];
+        let placements = [Placement::Fixed, Placement::FarthestFromStart, Placement::Diameter, Placement::RandomMinPath(3)];
+        let mut defs: Vec<LevelDef> = Vec::new();
+        for g in generators {
+            for pl in placements {
+                defs.push(with(sample(pl), |p| { p.generator = g; p.walls = WallPaint::default(); }));
+            }
+        }
+        // justo en los límites de lectura
+        defs.push(with(sample(Placement::RandomMinPath(MAX_CELLS * MAX_CELLS)), |p| {
+            p.cells = (MAX_CELLS, MAX_CELLS);
+            p.storeys = MAX_STOREYS;
+        }));
+        defs.push(with(sample(Placement::Fixed), |p| p.generator = Generator::Rooms(DungeonOptions {
+            max_rooms: MAX_CELLS, min_size: 1, max_size: MAX_CELLS, extra: 100,
+        })));
+        for (k, def) in defs.iter().enumerate() {
+            let code = level_code(def, k as u64).unwrap_or_else(|| panic!("nivel {k} sin código"));
+            assert!(level_from_code(&code, PathBuf::new()).is_ok(), "nivel {k}");
+        }
+    }
+
+    #[test]
+    fn levels_beyond_the_limits_have_no_code() {
+        let too_big: [fn(&mut ProcSpec); 5] = [
+            |p| p.cells = (MAX_CELLS + 1, 4),
+            |p| p.storeys = MAX_STOREYS + 1,
+            |p| p.placement = Placement::RandomMinPath(MAX_CELLS * MAX_CELLS + 1),
+            |p| p.generator = Generator::Rooms(DungeonOptions { max_rooms: MAX_CELLS + 1, ..Default::default() }),
+            |p| p.walls.painter = Painter::Voronoi { zones: MAX_CELLS + 1 },
+        ];
+        for (k, f) in too_big.into_iter().enumerate() {
+            assert!(level_code(&with(sample(Placement::Fixed), f), 1).is_none(), "caso {k}");
+        }
+    }
+}
@@ -0,0 +1,116 @@
+// src/solver.rs
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use crate::maze::Maze;
+use crate::tile::Tile;
+
+/// Celda del grid: (i, j) = (columna, fila), igual que `maze[(i, j)]`.
+pub type Cell = (usize, usize);
+
+/// Camino de celdas (incluye origen y destino); `length` = pasos.
+pub struct Route {
+    pub cells: Vec<Cell>,
+    pub length: usize,
+}
+
+impl Route {
+    pub fn from_cells(cells: Vec<Cell>) -> Self {
+        let length = cells.len().saturating_sub(1);
+        Self { cells, length }
+    }
+}
+
+fn neighbors(maze: &Maze, (i, j): Cell) -> impl Iterator<Item = Cell> + '_ {
+    [(1isize, 0isize), (0, 1), (-1, 0), (0, -1)].into_iter().filter_map(move |(dx, dy)| {
+        let ni = i.checked_add_signed(dx)?;
+        let nj = j.checked_add_signed(dy)?;
+        maze.get(ni, nj)?.is_walkable().then_some((ni, nj))
+    })
+}
+
+/// Distancias BFS (en pasos) desde una celda a todas las alcanzables.
+pub struct DistanceField {
+    w: usize,
+    dist: Vec<u32>,
+}
+
+const UNREACHED: u32 = u32::MAX;
+
+impl DistanceField {
+    pub fn get(&self, (i, j): Cell) -> Option<u32> {
+        self.dist.get(j * self.w + i).copied().filter(|&d| d != UNREACHED)
+    }
+
+    /// La celda alcanzable más lejana y su distancia.
+    pub fn farthest(&self) -> Option<(Cell, u32)> {
+        self.dist.iter().enumerate()
+            .filter(|&(_, &d)| d != UNREACHED)
+            .max_by_key(|&(k, &d)| (d, Reverse(k)))
+            .map(|(k, &d)| ((k % self.w, k / self.w), d))
+    }
+}
+
+/// BFS desde `from` sobre celdas caminables.
+pub fn distance_field(maze: &Maze, from: Cell) -> DistanceField {
+    let w = maze.width();
+    let mut dist = vec![UNREACHED; w * maze.height()];
+    let mut queue = VecDeque::new();
+    if maze.get(from.0, from.1).is_some_and(Tile::is_walkable) {
+        dist[from.1 * w + from.0] = 0;
+        queue.push_back(from);
+    }
+    while let Some(c) = queue.pop_front() {
+        let d = dist[c.1 * w + c.0];
+        for n in neighbors(maze, c) {
+            let k = n.1 * w + n.0;
+            if dist[k] == UNREACHED {
+                dist[k] = d + 1;
+                queue.push_back(n);
+            }
+        }
+    }
+    DistanceField { w, dist }
+}
+
+/// Camino más corto con A* (heurística Manhattan); explora menos que BFS
+/// cuando solo interesa un destino.
+pub fn astar(maze: &Maze, from: Cell, to: Cell) -> Option<Route> {
+    let w = maze.width();
+    let idx = |c: Cell| c.1 * w + c.0;
+    let hdist = |c: Cell| (c.0.abs_diff(to.0) + c.1.abs_diff(to.1)) as u32;
+
+    let mut g = vec![UNREACHED; w * maze.height()];
+    let mut came: Vec<Option<Cell>> = vec![None; w * maze.height()];
+    let mut open = BinaryHeap::new();
+    if !maze.get(from.0, from.1)?.is_walkable() { return None; }
+    g[idx(from)] = 0;
+    open.push(Reverse((hdist(from), 0u32, from)));
+
+    while let Some(Reverse((_, gc, c))) = open.pop() {
+        if c == to {
+            let mut cells = vec![c];
+            let mut cur = c;
+            while let Some(p) = came[idx(cur)] {
+                cells.push(p);
+                cur = p;
+            }
+            cells.reverse();
+            return Some(Route::from_cells(cells));
+        }
+        if gc > g[idx(c)] { continue; } // entrada vieja del heap
+        for n in neighbors(maze, c) {
+            let gn = gc + 1;
+            if gn < g[idx(n)] {
+                g[idx(n)] = gn;
+                came[idx(n)] = Some(c);
+                open.push(Reverse((gn + hdist(n), gn, n)));
+            }
+        }
+    }
+    None
+}
+
+/// Primera celda con el tile `tile` (en orden de lectura).
+pub fn find_tile(maze: &Maze, tile: Tile) -> Option<Cell> {
+    maze.find(tile)
+}
@@ -1,6 +1,12 @@
 use std::rc::Rc;
-use raylib::prelude::*;
-use crate::{framebuffer::Framebuffer, player::Player, texture::Texture};
+use raylib::color::Color;
+use crate::{
+    caster::RaySegment,
+    framebuffer::Framebuffer,
+    player::Player,
+    texture::Texture,
+    renderer3d::{Atmosphere, MIRROR_DIM},
+};
 
 #[derive(Clone)]
 pub struct SpriteAnim {
@@ -16,10 +22,20 @@ impl SpriteAnim {
     }
 }
 
+/// Qué punto del sprite queda a la altura `z`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SpriteAnchor {
+    Floor,   // la base en `z` (cajas, calcomanías de suelo)
+    Center,  // el centro en `z` (objetos flotando)
+    Ceiling, // el borde superior en `z` (lámparas, cadenas colgando)
+}
+
 pub struct Sprite {
     pub x: f32,
     pub y: f32,
-    pub size: f32,         // alto proyectado a 1.0 escala
+    pub z: f32,            // altura en mundo (0 = suelo, block_size = techo)
+    pub anchor: SpriteAnchor,
+    pub size: f32,         // alto en mundo (mismas unidades que block_size)
     pub anim: SpriteAnim,
     // --- parámetros de “temblor” (px y Hz) ---
     pub wobble_amp: f32,   // amplitud en PIXELES de pantalla
@@ -27,24 +43,49 @@ pub struct Sprite {
     pub phase: f32,        // desfase para no sincronizar
 }
 
+impl Sprite {
+    /// Altura en mundo del borde inferior, según el ancla.
+    fn bottom(&self) -> f32 {
+        match self.anchor {
+            SpriteAnchor::Floor   => self.z,
+            SpriteAnchor::Center  => self.z - self.size / 2.0,
+            SpriteAnchor::Ceiling => self.z - self.size,
+        }
+    }
+}
+
+/// Dibuja los sprites como billboards.
+/// `zbuf` es la profundidad de paredes por columna (de `render3d`) y `sbuf`
+/// la profundidad por píxel de sprites (`fb.width * fb.height`), que se
+/// escribe aquí para que solapes entre sprites se resuelvan por píxel.
+/// Se iluminan y funden con la niebla igual que las paredes (`atmo`).
+/// `eye_z` es la altura de la cámara en mundo (`block_size / 2` = horizonte
+/// de las paredes).
+/// Lo que se ve en espejos y portales se dibuja columna a columna siguiendo
+/// los tramos de `seen` (de `render3d`), con la distancia total recorrida.
+#[allow(clippy::too_many_arguments)]
 pub fn render_sprites(
     fb: &mut Framebuffer,
     player: &Player,
     sprites: &[Sprite],
     zbuf: &[f32],
+    seen: &[Vec<RaySegment>],
+    sbuf: &mut [f32],
     fov: f32,
     tsec: f32,
+    atmo: &Atmosphere,
+    eye_z: f32,
 ) {
     let hw = fb.width  as f32 / 2.0;
     let hh = fb.height as f32 / 2.0;
     let proj = hw / (fov / 2.0).tan();
+    let (ca, sa) = (player.a.cos(), player.a.sin());
 
+    // Orden de atrás hacia adelante por la MISMA profundidad que el z-buffer
+    // (perpendicular); el buffer por píxel corrige lo que el orden no resuelva.
+    let depth = |s: &Sprite| (s.x - player.pos.x) * ca + (s.y - player.pos.y) * sa;
     let mut order: Vec<usize> = (0..sprites.len()).collect();
-    order.sort_by(|&a, &b| {
-        let da = (sprites[a].x - player.pos.x).hypot(sprites[a].y - player.pos.y);
-        let db = (sprites[b].x - player.pos.x).hypot(sprites[b].y - player.pos.y);
-        db.partial_cmp(&da).unwrap()
-    });
+    order.sort_by(|&a, &b| depth(&sprites[b]).total_cmp(&depth(&sprites[a])));
 
     for &i in &order {
         let s = &sprites[i];
@@ -60,13 +101,16 @@ pub fn render_sprites(
         if delta.abs() > fov { continue; }
 
         // Distancia perpendicular (profundidad real)
-        let dist = dx * player.a.cos() + dy * player.a.sin();
+        let dist = depth(s);
         if dist <= 1.0 { continue; }
 
+        // Luz en la posición del sprite; niebla por distancia euclídea (como las paredes)
+        let light = atmo.light_at(s.x, s.y);
+        let fog_dist = dx.hypot(dy);
+
         // Tamaño proyectado
         let size = (proj / dist) * s.size;
         let w = size as i32;
-        let h = size as i32;
 
         // Centro X en pantalla
         let mut x_center = hw + (delta / (fov / 2.0)) * hw;
@@ -77,17 +121,16 @@ pub fn render_sprites(
         let woby = (s.wobble_amp * 0.6) * (omega * tsec * 1.1 + s.phase * 0.7).sin();
         x_center += wobx;
 
-        let ground_bias = (h as f32) * 0.70; // ~40% de su alto hacia abajo
+        // Proyección vertical: altura en mundo -> fila de pantalla
+        let z_bottom = s.bottom();
+        let screen_y = |z: f32| hh + (eye_z - z) * proj / dist;
+
         let x0 = (x_center - w as f32 / 2.0).round() as i32;
         let x1 = (x_center + w as f32 / 2.0).round() as i32;
-        let y0 = (hh - h as f32 / 2.0 + woby + ground_bias).round() as i32;
-        let y1 = (hh + h as f32 / 2.0 + woby + ground_bias).round() as i32;
-
-
+        let y0 = (screen_y(z_bottom + s.size) + woby).round() as i32;
+        let y1 = (screen_y(z_bottom) + woby).round() as i32;
 
         let tex = s.anim.frame_at(tsec);
-        let tw = tex.w as i32;
-        let th = tex.h as i32;
 
         for xs in x0.max(0)..=x1.min(fb.width as i32 - 1) {
             // depth-test con paredes
@@ -95,23 +138,74 @@ pub fn render_sprites(
             if dist >= zb { continue; }
 
             let u = (xs - x0) as f32 / (x1 - x0).max(1) as f32;
-            for ys in y0.max(0)..=y1.min(fb.height as i32 - 1) {
-                let v = (ys - y0) as f32 / (y1 - y0).max(1) as f32;
+            draw_column(fb, sbuf, tex, xs, u, (y0, y1), dist, |pix| atmo.shade(pix, fog_dist, light, 1.0));
+        }
+    }
 
-                let tx = ((u * tex.w as f32) as i32).clamp(0, tw - 1);
-                let ty = ((v * tex.h as f32) as i32).clamp(0, th - 1);
-                let pix = tex.px[(ty as u32 * tex.w + tx as u32) as usize];
+    // Reflejos y portales: cada tramo es un rayo recto desde otro origen; un
+    // sprite entra en la columna si el tramo pasa a menos de medio ancho de él
+    for (xs, segs) in seen.iter().enumerate().take(fb.width as usize) {
+        if segs.is_empty() { continue; }
+        let ray_a = player.a - fov / 2.0 + fov * xs as f32 / fb.width as f32;
+        let cos_view = (ray_a - player.a).cos();
+
+        for (k, seg) in segs.iter().enumerate() {
+            let (sc, ss) = (seg.a.cos(), seg.a.sin());
+            let dim = MIRROR_DIM.powi(k as i32 + 1);
+
+            for s in sprites {
+                let (dx, dy) = (s.x - seg.x, s.y - seg.y);
+                let along = dx * sc + dy * ss;
+                let across = sc * dy - ss * dx;
+                let travelled = seg.start + along;
+                if along <= 0.0 || travelled >= seg.end || across.abs() >= s.size / 2.0 { continue; }
+
+                let dist = travelled * cos_view;
+                if dist <= 1.0 { continue; }
+
+                let z_bottom = s.bottom();
+                let screen_y = |z: f32| (hh + (eye_z - z) * proj / dist).round() as i32;
+                let (y0, y1) = (screen_y(z_bottom + s.size), screen_y(z_bottom));
+                let u = 0.5 - across / s.size;
+                let light = atmo.light_at(s.x, s.y);
+                let tex = s.anim.frame_at(tsec);
+                draw_column(fb, sbuf, tex, xs as i32, u, (y0, y1), dist, |pix| atmo.shade(pix, travelled, light, dim));
+            }
+        }
+    }
+}
 
-                let a = ((pix >> 24) & 0xFF) as u8;
-                if a < 10 { continue; }
+/// Una columna de un sprite: texel `u` fijo, de `y0` a `y1` en pantalla,
+/// con test de profundidad por píxel contra `sbuf`.
+#[allow(clippy::too_many_arguments)]
+fn draw_column(
+    fb: &mut Framebuffer,
+    sbuf: &mut [f32],
+    tex: &Texture,
+    xs: i32,
+    u: f32,
+    (y0, y1): (i32, i32),
+    dist: f32,
+    shade: impl Fn(u32) -> Color,
+) {
+    let tw = tex.w as i32;
+    let th = tex.h as i32;
+    let tx = ((u * tex.w as f32) as i32).clamp(0, tw - 1);
 
-                let r = ((pix >> 16) & 0xFF) as u8;
-                let g = ((pix >> 8)  & 0xFF) as u8;
-                let b = ( pix        & 0xFF) as u8;
+    for ys in y0.max(0)..=y1.min(fb.height as i32 - 1) {
+        // depth-test con otros sprites
+        let idx = ys as usize * fb.width as usize + xs as usize;
+        if dist >= sbuf[idx] { continue; }
 
-                fb.set_current_color(Color::new(r, g, b, a));
-                fb.set_pixel(xs as u32, ys as u32);
-            }
-        }
+        let v = (ys - y0) as f32 / (y1 - y0).max(1) as f32;
+        let ty = ((v * tex.h as f32) as i32).clamp(0, th - 1);
+        let pix = tex.px[(ty as u32 * tex.w + tx as u32) as usize];
+
+        let a = ((pix >> 24) & 0xFF) as u8;
+        if a < 10 { continue; }
+
+        sbuf[idx] = dist;
+        fb.set_current_color(shade(pix));
+        fb.set_pixel(xs as u32, ys as u32);
     }
 }
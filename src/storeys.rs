@@ -0,0 +1,160 @@
+// src/storeys.rs
+use std::collections::VecDeque;
+use crate::maze::Maze;
+use crate::solver::{distance_field, Cell};
+use crate::tile::Tile;
+
+/// Casilla en un nivel de varios pisos: (piso, celda).
+pub type Spot = (usize, Cell);
+
+/// Con qué se unen los pisos de un nivel generado.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Link {
+    #[default]
+    Stairs,
+    Elevator,
+}
+
+/// Piso al que lleva la casilla `c` del piso `s`, si es una escalera o un
+/// ascensor con salida: la escalera sube o baja un piso y el ascensor va al
+/// siguiente piso hacia arriba que tenga ascensor en la misma casilla
+/// (desde el último vuelve al primero). La casilla de llegada tiene que ser
+/// transitable.
+pub fn destination(storeys: &[Maze], s: usize, c: Cell) -> Option<usize> {
+    let walkable = |t: usize| storeys.get(t).and_then(|m| m.get(c.0, c.1)).is_some_and(Tile::is_walkable);
+    let to = match storeys.get(s)?.get(c.0, c.1)? {
+        Tile::StairsUp   => s + 1,
+        Tile::StairsDown => s.checked_sub(1)?,
+        Tile::Elevator   => (1..storeys.len())
+            .map(|k| (s + k) % storeys.len())
+            .find(|&t| storeys[t].get(c.0, c.1) == Some(Tile::Elevator))?,
+        _ => return None,
+    };
+    walkable(to).then_some(to)
+}
+
+/// Camino más corto (BFS) desde `from` a la meta más cercana de cualquier
+/// piso; cambiar de piso cuenta como un paso. Incluye origen y destino.
+pub fn route_to_goal(storeys: &[Maze], from: Spot) -> Option<Vec<Spot>> {
+    let w = storeys.first()?.width();
+    let per = w * storeys[0].height();
+    let idx = |(s, (i, j)): Spot| s * per + j * w + i;
+    let mut came: Vec<Option<Spot>> = vec![None; per * storeys.len()];
+    let mut seen = vec![false; per * storeys.len()];
+    let mut queue = VecDeque::new();
+    if !storeys.get(from.0)?.get(from.1.0, from.1.1)?.is_walkable() { return None; }
+    seen[idx(from)] = true;
+    queue.push_back(from);
+
+    while let Some(spot @ (s, (i, j))) = queue.pop_front() {
+        if storeys[s][(i, j)] == Tile::Goal {
+            let mut path = vec![spot];
+            let mut cur = spot;
+            while let Some(p) = came[idx(cur)] {
+                path.push(p);
+                cur = p;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let walk = [(1isize, 0isize), (0, 1), (-1, 0), (0, -1)].into_iter().filter_map(|(dx, dy)| {
+            let c = (i.checked_add_signed(dx)?, j.checked_add_signed(dy)?);
+            storeys[s].get(c.0, c.1)?.is_walkable().then_some((s, c))
+        });
+        let climb = destination(storeys, s, (i, j)).map(|t| (t, (i, j)));
+        for next in walk.chain(climb) {
+            if !seen[idx(next)] {
+                seen[idx(next)] = true;
+                came[idx(next)] = Some(spot);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Ruta desde el inicio 'p' (en el piso que esté) a la meta más cercana.
+pub fn solve(storeys: &[Maze]) -> Option<Vec<Spot>> {
+    let start = storeys.iter().enumerate().find_map(|(s, m)| m.find(Tile::Start).map(|c| (s, c)))?;
+    route_to_goal(storeys, start)
+}
+
+/// Une pisos generados por separado (todos del mismo tamaño) en un solo
+/// recorrido: el inicio queda en el primero y la meta en el último. En cada
+/// piso la subida va en la casilla más lejana (a pie) de donde se llega, que
+/// además sea suelo en el piso de arriba. Devuelve los pasos del recorrido,
+/// o `None` si en algún piso no queda suelo alcanzable para la meta (solo se
+/// llega al inicio o a la bajada, que no se pisan).
+pub fn stack(storeys: &mut [Maze], link: Link) -> Option<u32> {
+    let Some(mut entry) = storeys.first().and_then(|m| m.find(Tile::Start)) else { return Some(0) };
+    for (s, m) in storeys.iter_mut().enumerate() {
+        for j in 0..m.height() {
+            for i in 0..m.width() {
+                let t = m[(i, j)];
+                if t == Tile::Goal || (s > 0 && t == Tile::Start) { m[(i, j)] = Tile::Floor; }
+            }
+        }
+    }
+
+    let mut total = 0;
+    let last = storeys.len() - 1;
+    for s in 0..=last {
+        let field = distance_field(&storeys[s], entry);
+        let up_ok = |c: Cell| s < last && storeys[s + 1][c] == Tile::Floor;
+        let exit = storeys[s].cells()
+            .filter(|&(c, t)| t == Tile::Floor && (s == last || up_ok(c)))
+            .filter_map(|(c, _)| field.get(c).map(|d| (d, c)))
+            .max_by_key(|&(d, c)| (d, std::cmp::Reverse(c)));
+        let Some((d, c)) = exit else {
+            // Sin casilla común con el piso de arriba: la meta se queda aquí,
+            // en el suelo más lejano (la entrada no es suelo: nunca se pisa)
+            let (d, c) = storeys[s].cells()
+                .filter(|&(_, t)| t == Tile::Floor)
+                .filter_map(|(c, _)| field.get(c).map(|d| (d, c)))
+                .max_by_key(|&(d, c)| (d, std::cmp::Reverse(c)))?;
+            storeys[s][c] = Tile::Goal;
+            return Some(total + d);
+        };
+        total += d;
+        if s == last {
+            storeys[s][c] = Tile::Goal;
+        } else {
+            let (up, down) = match link {
+                Link::Stairs   => (Tile::StairsUp, Tile::StairsDown),
+                Link::Elevator => (Tile::Elevator, Tile::Elevator),
+            };
+            storeys[s][c] = up;
+            storeys[s + 1][c] = down;
+            total += 1;
+            entry = c;
+        }
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storey(rows: &[&str]) -> Maze {
+        Maze::from_rows(rows.iter().map(|r| r.chars().map(|c| Tile::from_char(c).unwrap()).collect()).collect())
+    }
+
+    #[test]
+    fn goal_never_replaces_start_or_stairs() {
+        // arriba solo se llega a la bajada: no hay dónde poner la meta
+        let mut storeys = vec![storey(&["#####", "#p  #", "#####"]), storey(&["#####", "### #", "#####"])];
+        assert_eq!(stack(&mut storeys, Link::Stairs), None);
+        assert_eq!(storeys[1][(3, 1)], Tile::StairsDown);
+
+        // un solo piso con solo el inicio
+        let mut alone = vec![storey(&["###", "#p#", "###"])];
+        assert_eq!(stack(&mut alone, Link::Stairs), None);
+        assert_eq!(alone[0][(1, 1)], Tile::Start);
+
+        let mut storeys = vec![storey(&["#####", "#p  #", "#####"]), storey(&["#####", "#   #", "#####"])];
+        assert!(stack(&mut storeys, Link::Elevator).is_some());
+        assert_eq!(storeys[0].find(Tile::Start), Some((1, 1)));
+        assert!(solve(&storeys).is_some_and(|r| r.len() > 1));
+    }
+}
@@ -1,4 +1,4 @@
-use image::{GenericImageView, ImageError};
+use image::ImageError;
 
 pub struct Texture {
     pub w: u32,
@@ -20,6 +20,15 @@ impl Texture {
         Ok(Self { w, h, px })
     }
 
+    /// Copia con cada canal multiplicado por `tint` (255 = sin cambio).
+    pub fn tinted(&self, tint: [u8; 3]) -> Self {
+        let px = self.px.iter().map(|&p| {
+            let ch = |shift: u32, k: usize| ((p >> shift & 0xFF) * tint[k] as u32 / 255) << shift;
+            (p & 0xFF00_0000) | ch(16, 0) | ch(8, 1) | ch(0, 2)
+        }).collect();
+        Self { w: self.w, h: self.h, px }
+    }
+
     #[inline]
     pub fn sample(&self, mut u: f32, mut v: f32) -> u32 {
         // wrap 0..1
@@ -2,10 +2,12 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 use crate::texture::Texture;
+use crate::tile::Tile;
 
+#[derive(Clone)]
 pub struct TextureAtlas {
     default: Rc<Texture>,
-    map: HashMap<char, Rc<Texture>>,
+    map: HashMap<Tile, Rc<Texture>>,
 }
 
 impl TextureAtlas {
@@ -13,11 +15,11 @@ impl TextureAtlas {
         Self { default, map: HashMap::new() }
     }
 
-    pub fn insert(&mut self, tile: char, tex: Rc<Texture>) {
+    pub fn insert(&mut self, tile: Tile, tex: Rc<Texture>) {
         self.map.insert(tile, tex);
     }
 
-    pub fn get(&self, tile: char) -> &Texture {
+    pub fn get(&self, tile: Tile) -> &Texture {
         self.map.get(&tile).unwrap_or(&self.default).as_ref()
     }
 }
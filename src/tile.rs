@@ -0,0 +1,142 @@
+// src/tile.rs
+use raylib::color::Color;
+
+/// Tipo de cada casilla del mapa.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tile {
+    Floor,
+    Wall,
+    Stone,
+    Moss,       // materiales de muro extra (ver `painters`)
+    DarkBrick,
+    DarkStone,
+    Sandstone,
+    Mirror,
+    Portal(u8), // 1..=9; las dos casillas con el mismo número quedan enlazadas
+    Start,
+    Goal,
+    StairsUp,   // lleva al piso de arriba (misma casilla)
+    StairsDown, // lleva al de abajo
+    Elevator,   // al siguiente piso con ascensor en la misma casilla (da la vuelta)
+}
+
+/// Propiedades de un tipo de tile. Para agregar uno nuevo basta con una
+/// variante en `Tile` y su fila en `TILES`.
+pub struct TileInfo {
+    pub tile: Tile,
+    pub ch: char,                      // carácter en archivos de texto
+    pub solid: bool,                   // bloquea al jugador
+    pub opaque: bool,                  // detiene los rayos (se dibuja como pared)
+    pub mirror: bool,                  // refleja los rayos
+    pub texture: Option<&'static str>, // archivo en assets/ (None = la de por defecto)
+    pub tint: [u8; 3],                 // se multiplica a la textura (blanco = tal cual)
+    pub minimap: Color,
+}
+
+/// Registro de tiles. Los portales comparten fila (el carácter real es su dígito).
+pub const TILES: [TileInfo; 14] = [
+    TileInfo { tile: Tile::Floor,      ch: ' ', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::new(255, 255, 255, 40) },
+    TileInfo { tile: Tile::Wall,       ch: '#', solid: true,  opaque: true,  mirror: false, texture: Some("bricks.jpg"),
+                tint: WHITE, minimap: Color::DARKGRAY },
+    TileInfo { tile: Tile::Stone,      ch: 'A', solid: true,  opaque: true,  mirror: false, texture: Some("stone.jpg"),
+                tint: WHITE, minimap: Color::GRAY },
+    TileInfo { tile: Tile::Moss,       ch: 'V', solid: true,  opaque: true,  mirror: false, texture: Some("stone.jpg"),
+                tint: [140, 190, 120], minimap: Color::new(70, 110, 60, 255) },
+    TileInfo { tile: Tile::DarkBrick,  ch: 'D', solid: true,  opaque: true,  mirror: false, texture: Some("bricks.jpg"),
+                tint: [130, 120, 120], minimap: Color::new(50, 40, 40, 255) },
+    TileInfo { tile: Tile::DarkStone,  ch: 'N', solid: true,  opaque: true,  mirror: false, texture: Some("stone.jpg"),
+                tint: [90, 90, 105], minimap: Color::new(45, 45, 55, 255) },
+    TileInfo { tile: Tile::Sandstone,  ch: 'R', solid: true,  opaque: true,  mirror: false, texture: Some("stone.jpg"),
+                tint: [235, 200, 140], minimap: Color::new(190, 160, 110, 255) },
+    TileInfo { tile: Tile::Mirror,     ch: 'M', solid: true,  opaque: true,  mirror: true,  texture: None,
+                tint: WHITE, minimap: Color::new(170, 220, 255, 255) },
+    TileInfo { tile: Tile::Portal(1),  ch: '1', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::PURPLE },
+    TileInfo { tile: Tile::Start,      ch: 'p', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::SKYBLUE },
+    TileInfo { tile: Tile::Goal,       ch: 'g', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::GREEN },
+    TileInfo { tile: Tile::StairsUp,   ch: '<', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::ORANGE },
+    TileInfo { tile: Tile::StairsDown, ch: '>', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::BROWN },
+    TileInfo { tile: Tile::Elevator,   ch: 'E', solid: false, opaque: false, mirror: false, texture: None,
+                tint: WHITE, minimap: Color::GOLD },
+];
+
+const WHITE: [u8; 3] = [255, 255, 255];
+
+
+impl Tile {
+    pub fn info(self) -> &'static TileInfo {
+        match self {
+            Tile::Floor      => &TILES[0],
+            Tile::Wall       => &TILES[1],
+            Tile::Stone      => &TILES[2],
+            Tile::Moss       => &TILES[3],
+            Tile::DarkBrick  => &TILES[4],
+            Tile::DarkStone  => &TILES[5],
+            Tile::Sandstone  => &TILES[6],
+            Tile::Mirror     => &TILES[7],
+            Tile::Portal(_)  => &TILES[8],
+            Tile::Start      => &TILES[9],
+            Tile::Goal       => &TILES[10],
+            Tile::StairsUp   => &TILES[11],
+            Tile::StairsDown => &TILES[12],
+            Tile::Elevator   => &TILES[13],
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Tile> {
+        if let Some(d) = c.to_digit(10).filter(|&d| d > 0) {
+            return Some(Tile::Portal(d as u8));
+        }
+        TILES.iter().find(|t| t.ch == c && !t.tile.is_portal()).map(|t| t.tile)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Portal(n) => char::from(b'0' + n),
+            t => t.info().ch,
+        }
+    }
+
+    #[inline]
+    pub fn is_solid(self) -> bool {
+        self.info().solid
+    }
+
+    #[inline]
+    pub fn is_walkable(self) -> bool {
+        !self.info().solid
+    }
+
+    #[inline]
+    pub fn is_opaque(self) -> bool {
+        self.info().opaque
+    }
+
+    #[inline]
+    pub fn is_mirror(self) -> bool {
+        self.info().mirror
+    }
+
+    /// Muro común (no espejo): lo que los pintores de materiales pueden cambiar.
+    #[inline]
+    pub fn is_material(self) -> bool {
+        let info = self.info();
+        info.solid && info.opaque && !info.mirror
+    }
+
+    #[inline]
+    pub fn is_portal(self) -> bool {
+        matches!(self, Tile::Portal(_))
+    }
+
+    /// Escaleras o ascensor: cambian de piso al entrar.
+    #[inline]
+    pub fn is_storey_link(self) -> bool {
+        matches!(self, Tile::StairsUp | Tile::StairsDown | Tile::Elevator)
+    }
+}